@@ -72,20 +72,27 @@ bpy.context.scene.frame_start = 0
 bpy.context.scene.frame_end = NUM_FRAMES*FRAMES_SPACING
 
 #cloth_ptr = lib.get_cloth_mesh(5,10,10)
-#cloth_ptr = lib.get_woven_cloth_mesh(5,15,5, 120, 0.5)
-cloth_ptr = lib.get_cloth_mesh_field(5,10,5, 120, +3.0, 0.5)
+#cloth_ptr = lib.get_woven_cloth_mesh(5,15,5, 120, 0.5, 0.5, 0.0)
+cloth_ptr = lib.get_cloth_mesh_field(5,10,5, 120, +3.0, 0.5, 0, 0.5, 0.0)
+
+# solver log lines to Blender's console; 4 shows connector changes and warnings, 5 every step
+@ffi.callback("LogCallback")
+def log_to_console(level, line, _user_data):
+    print(ffi.string(line).decode())
+
+lib.set_cloth_mesh_log_callback(cloth_ptr, log_to_console, ffi.NULL, 4)
 gp_layer = init_grease_pencil()
 
 for frame in range(NUM_FRAMES):
     gp_frame = gp_layer.frames.new(frame*FRAMES_SPACING)
     node_count = lib.mesh_node_count(cloth_ptr)
     node_pos_d = None
-    node_positions = [lib.mesh_node_position(cloth_ptr, i) for i in range(node_count)]
+    node_positions = [lib.mesh_node_position3(cloth_ptr, i) for i in range(node_count)]
     
     #node_pos_d = None
     #for node_pos in node_positions:
     #    if node_pos_d:
-    #        draw_line(gp_frame, (0, node_pos.x, node_pos.y), (0, node_pos_d.x, node_pos_d.y))
+    #        draw_line(gp_frame, (node_pos.z, node_pos.x, node_pos.y), (node_pos_d.z, node_pos_d.x, node_pos_d.y))
     #    node_pos_d = node_pos
 
 
@@ -98,11 +105,14 @@ for frame in range(NUM_FRAMES):
         
     connector_positions = []
     for inter_index in range(inter_con_count):
-        connector_positions += [lib.get_interconnector_constraint(cloth_ptr, inter_index, index) for index in range(constraint_counts[inter_index])]
+        connector_positions += [lib.get_interconnector_constraint3(cloth_ptr, inter_index, index) for index in range(constraint_counts[inter_index])]
     
     for node in connector_positions:
-        draw_line(gp_frame, (0, node.x, node.y), (0, node.dx, node.dy))
+        draw_line(gp_frame, (node.z, node.x, node.y), (node.dz, node.dx, node.dy))
     
-    lib.update_cloth_mesh(cloth_ptr, 0.016, 3)
+    if lib.update_cloth_mesh(cloth_ptr, 0.016, 3) != lib.NacStatus_Ok:
+        print(ffi.string(lib.nac_last_error()).decode())
+        break
 
+lib.clear_cloth_mesh_log_callback(cloth_ptr)
 lib.mesh_free(cloth_ptr)
\ No newline at end of file
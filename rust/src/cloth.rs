@@ -1,49 +1,75 @@
-use crate::nac::{Node, SharedNode, SharedNodes, InterConnector, SelfConnector, Mesh};
-use crate::verlet::Verlet;
-use crate::verlet::{gravity_constraint, wind_constraint, internode_constraint, force_constraint, ground_boundary_constraint, ground_bound_gravity_constraint};
+use crate::nac::{Node, SharedNode, SharedNodes, InterConnector, SelfConnector, InterStepResolve, SelfStepResolve, Mesh, Solver, Convergence, ResidualNorm, SolveReport, Integrator, read_node, write_node};
+use crate::error::{NacError, NacStatus, guarded, status, checked, non_null, non_null_mut};
+use crate::verlet::{Verlet3, VerletNode, Point, Point3};
+use crate::constraint::{Constraint, Tear};
+use crate::integrator::{VerletIntegrator, SemiImplicitEuler, Rk4};
+use crate::snapshot::MeshSnapshot;
+use crate::scene::load_mesh;
+use crate::handle::{NodeHandle, InterHandle, SelfHandle};
+use crate::poison::PoisonPolicy;
+use crate::logging::{self, LogCallback, CallbackDrain};
+use crate::tear::{Break, BreakCallback, CBreakCallback};
+use crate::damping::Damping;
 
 use std::sync::{Arc, RwLock};
-use std::convert::TryInto;
+use std::convert::TryFrom;
 
-use std::ffi::CStr;
-use std::os::raw::c_char;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+
+use slog::Level;
+
+// The builders' gravity and wind are how far they move a node in a step of this dt, and are
+// rescaled to accelerations so the cloth follows whatever dt the mesh is updated with
+const REFERENCE_DT: f64 = 0.016;
+
+// gravity that stepped by `delta` every update, as one that falls the same at REFERENCE_DT
+fn builder_gravity(gravity: i16, delta: f64) -> f64 {
+    f64::from(gravity) * (delta / REFERENCE_DT).powi(2)
+}
+
+// wind that moved nodes by up to `strength` every update, as an acceleration over REFERENCE_DT
+fn builder_wind(strength: f64, seed: u64) -> Constraint {
+    Constraint::accelerated_wind(strength / REFERENCE_DT.powi(2), 0.0, seed)
+}
 
 pub trait Cloth {
-    fn new_cloth(height: u8, width: u8, spacing: u8, gravity: i16, wind: f64, spring: f64) -> Self;
+    #[allow(clippy::too_many_arguments)]
+    fn new_cloth(height: u8, width: u8, spacing: u8, gravity: i16, wind: f64, spring: f64, seed: u64, damping: Damping) -> Self;
     fn empty_cloth() -> Self;
-    fn cloth_boundaries(height: u8, width: u8, spacing: u8) -> Self;
-    fn cloth_interweave(height: u8, width: u8, spacing: u8, gravity: i16, spring: f64) -> Self;
+    fn cloth_boundaries(height: u8, width: u8, spacing: u8, seed: u64) -> Self;
+    fn cloth_interweave(height: u8, width: u8, spacing: u8, gravity: i16, spring: f64, seed: u64, damping: Damping) -> Self;
 }
 
-impl Cloth for Mesh<Verlet> {
+impl<V: VerletNode> Cloth for Mesh<V> {
     // TODO(kevinc) make delarative and not imperative
-    fn new_cloth(height: u8, width: u8, spacing: u8, gravity: i16, wind: f64, spring: f64) -> Mesh<Verlet> {
-        let mut nodes: Vec<SharedNode<Verlet>> = vec!();
-        let mut interconnectors: Vec<InterConnector<Verlet>> = vec!();
-        let mut selfconnectors: Vec<SelfConnector<Verlet>> = vec!();
-        let gravity = move |node: &Node<Verlet>| gravity_constraint(node, 0.001, gravity);
-        let wind = move |node: &Node<Verlet>| wind_constraint(node, wind,-0.00);
-        let cloth_constraint = move |node1: &Node<Verlet>, node2: &Node<Verlet>| internode_constraint(node1,node2,spacing.clone() as f64, spring);
+    fn new_cloth(height: u8, width: u8, spacing: u8, gravity: i16, wind: f64, spring: f64, seed: u64, damping: Damping) -> Mesh<V> {
+        let mut nodes: Vec<SharedNode<V>> = vec!();
+        let mut interconnectors: Vec<InterConnector<V>> = vec!();
+        let mut selfconnectors: Vec<SelfConnector<V>> = vec!();
+        let gravity: Arc<dyn SelfStepResolve<V>> = Arc::new(Constraint::Gravity { gravity: builder_gravity(gravity, 0.001), delta: None });
+        let wind: Arc<dyn SelfStepResolve<V>> = Arc::new(builder_wind(wind, seed));
+        let cloth_constraint: Arc<dyn InterStepResolve<V>> = Arc::new(Constraint::Distance { spacing: spacing as f64, spring, tear: None });
         for y in 0..height {
             for x in 0..width {
-                let p: SharedNode<Verlet>;
+                let p: SharedNode<V>;
                 if y == 0 {
-                    p = Arc::new(RwLock::new(Node::new(Verlet::new_pinned(f64::from(spacing*x),f64::from(0.0)))));
+                    p = Arc::new(RwLock::new(Node::new(V::new_pinned_at(V::Point::planar(f64::from(spacing*x),f64::from(0.0))))));
                 } else {
-                    p = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(x*spacing),f64::from(y*spacing)))));
-                    let selfconnector = SelfConnector::new("gravity", Arc::clone(&p),Arc::new(gravity));
+                    p = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(x*spacing),f64::from(y*spacing))))));
+                    let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&p),Arc::clone(&gravity));
                     selfconnectors.push(selfconnector);
-                    let selfconnector = SelfConnector::new("wind", Arc::clone(&p),Arc::new(wind));
+                    let selfconnector = SelfConnector::stepped("wind", Arc::clone(&p),Arc::clone(&wind));
                     selfconnectors.push(selfconnector);
 
                 }
 
                 if x != 0 {
-                   let interconnector = InterConnector::new(Arc::clone(&p), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+                   let interconnector = InterConnector::stepped(Arc::clone(&p), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("horizontal");
                     interconnectors.push(interconnector);
                 }
                 if y != 0 {
-                   let interconnector = InterConnector::new(Arc::clone(&p), Some(vec!(Arc::clone(&nodes[(x+(y-1) * width) as usize]))), Arc::new(cloth_constraint));
+                   let interconnector = InterConnector::stepped(Arc::clone(&p), Some(vec!(Arc::clone(&nodes[(x+(y-1) * width) as usize]))), Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("vertical");
                     interconnectors.push(interconnector);
                 }
 
@@ -51,116 +77,118 @@ impl Cloth for Mesh<Verlet> {
             }
         }
 
-        Mesh::new(nodes, interconnectors, selfconnectors)
+        let mut mesh = Mesh::new(nodes, interconnectors, selfconnectors);
+        mesh.set_damping(damping);
+        mesh
 
     }
 
-    fn empty_cloth() -> Mesh<Verlet> {
+    fn empty_cloth() -> Mesh<V> {
         Mesh::new(vec![], vec![], vec![])
     }
 
-    fn cloth_boundaries(height: u8, width: u8, spacing: u8) -> Mesh<Verlet> {
-        let mut nodes: Vec<SharedNode<Verlet>> = vec!();
-        let mut interconnectors: Vec<InterConnector<Verlet>> = vec!();
-        let mut selfconnectors: Vec<SelfConnector<Verlet>> = vec!();
-        let gravity = move |node: &Node<Verlet>| gravity_constraint(node, 0.0016,1200);
-        let wind = move |node: &Node<Verlet>| wind_constraint(node, -3.0,-0.00);
-        let cloth_constraint = move |node1: &Node<Verlet>, node2: &Node<Verlet>| internode_constraint(node1,node2,spacing.clone() as f64, 0.5);
+    fn cloth_boundaries(height: u8, width: u8, spacing: u8, seed: u64) -> Mesh<V> {
+        let mut nodes: Vec<SharedNode<V>> = vec!();
+        let mut interconnectors: Vec<InterConnector<V>> = vec!();
+        let mut selfconnectors: Vec<SelfConnector<V>> = vec!();
+        let gravity: Arc<dyn SelfStepResolve<V>> = Arc::new(Constraint::Gravity { gravity: builder_gravity(1200, 0.0016), delta: None });
+        let wind: Arc<dyn SelfStepResolve<V>> = Arc::new(builder_wind(-3.0, seed));
+        let cloth_constraint: Arc<dyn InterStepResolve<V>> = Arc::new(Constraint::Distance { spacing: spacing as f64, spring: 0.5, tear: None });
 
-        let top_left = Arc::new(RwLock::new(Node::new(Verlet::new_pinned(f64::from(0),f64::from(0)))));
-        let top_right = Arc::new(RwLock::new(Node::new(Verlet::new_pinned(f64::from(width*spacing),f64::from(0)))));
-        let bottom_left = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(0),f64::from(spacing*height)))));
-        let bottom_right = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(spacing*width),f64::from(spacing*height)))));
+        let top_left = Arc::new(RwLock::new(Node::new(V::new_pinned_at(V::Point::planar(f64::from(0),f64::from(0))))));
+        let top_right = Arc::new(RwLock::new(Node::new(V::new_pinned_at(V::Point::planar(f64::from(width*spacing),f64::from(0))))));
+        let bottom_left = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(0),f64::from(spacing*height))))));
+        let bottom_right = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(spacing*width),f64::from(spacing*height))))));
 
         for x in 1..width {
-            let p_up = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(x*spacing),f64::from(0)))));
+            let p_up = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(x*spacing),f64::from(0))))));
             if x == 1 {
-                let interconnector = InterConnector::new(Arc::clone(&p_up), Some(vec!(Arc::clone(&top_left))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p_up), Some(vec!(Arc::clone(&top_left))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("top");
                 interconnectors.push(interconnector);
             } else {
-                let interconnector = InterConnector::new(Arc::clone(&p_up), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p_up), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("top");
                 interconnectors.push(interconnector);
             }
 
-            let selfconnector = SelfConnector::new("gravity", Arc::clone(&p_up),Arc::new(gravity));
+            let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&p_up),Arc::clone(&gravity));
             selfconnectors.push(selfconnector);
             
             nodes.push(p_up);
         }
-        let interconnector = InterConnector::new(Arc::clone(&top_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+        let interconnector = InterConnector::stepped(Arc::clone(&top_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("top");
         interconnectors.push(interconnector);
 
-        let selfconnector = SelfConnector::new("gravity", Arc::clone(&top_right),Arc::new(gravity));
+        let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&top_right),Arc::clone(&gravity));
         selfconnectors.push(selfconnector);
         
         for x in 1..width {
-            let p = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(x*spacing),f64::from(height*spacing)))));
+            let p = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(x*spacing),f64::from(height*spacing))))));
             if x == 1 {
-                let interconnector = InterConnector::new(Arc::clone(&p), Some(vec!(Arc::clone(&bottom_left))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p), Some(vec!(Arc::clone(&bottom_left))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("bottom");
                 interconnectors.push(interconnector);
             } else {
-                let interconnector = InterConnector::new(Arc::clone(&p), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("bottom");
                 interconnectors.push(interconnector);
             }
 
-            let selfconnector = SelfConnector::new("gravity", Arc::clone(&p),Arc::new(gravity));
+            let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&p),Arc::clone(&gravity));
             selfconnectors.push(selfconnector);
-            let selfconnector = SelfConnector::new("wind", Arc::clone(&p),Arc::new(wind));
+            let selfconnector = SelfConnector::stepped("wind", Arc::clone(&p),Arc::clone(&wind));
             selfconnectors.push(selfconnector);
             
             nodes.push(p);
         }
-        let interconnector = InterConnector::new(Arc::clone(&bottom_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+        let interconnector = InterConnector::stepped(Arc::clone(&bottom_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("bottom");
         interconnectors.push(interconnector);
 
-        let selfconnector = SelfConnector::new("gravity", Arc::clone(&bottom_right),Arc::new(gravity));
+        let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&bottom_right),Arc::clone(&gravity));
         selfconnectors.push(selfconnector);
-        let selfconnector = SelfConnector::new("wind", Arc::clone(&bottom_right),Arc::new(wind));
+        let selfconnector = SelfConnector::stepped("wind", Arc::clone(&bottom_right),Arc::clone(&wind));
         selfconnectors.push(selfconnector);
         
 
 
         for y in 1..height {
-            let p = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(0),f64::from(y*spacing)))));
+            let p = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(0),f64::from(y*spacing))))));
             if y == 1 {
-                let interconnector = InterConnector::new(Arc::clone(&p), Some(vec!(Arc::clone(&top_left))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p), Some(vec!(Arc::clone(&top_left))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("left");
                 interconnectors.push(interconnector);
             } else {
-                let interconnector = InterConnector::new(Arc::clone(&p), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("left");
                 interconnectors.push(interconnector);
             }
 
-            let selfconnector = SelfConnector::new("gravity", Arc::clone(&p),Arc::new(gravity));
+            let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&p),Arc::clone(&gravity));
             selfconnectors.push(selfconnector);
             
             nodes.push(p);
         }
-        let interconnector = InterConnector::new(Arc::clone(&bottom_left), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+        let interconnector = InterConnector::stepped(Arc::clone(&bottom_left), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("left");
         interconnectors.push(interconnector);
 
-        let selfconnector = SelfConnector::new("gravity", Arc::clone(&bottom_left),Arc::new(gravity));
+        let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&bottom_left),Arc::clone(&gravity));
         selfconnectors.push(selfconnector);
 
 
         for y in 1..height {
-            let p = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(width*spacing),f64::from(y*spacing)))));
+            let p = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(width*spacing),f64::from(y*spacing))))));
             if y == 1 {
-                let interconnector = InterConnector::new(Arc::clone(&p), Some(vec!(Arc::clone(&top_right))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p), Some(vec!(Arc::clone(&top_right))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("right");
                 interconnectors.push(interconnector);
             } else {
-                let interconnector = InterConnector::new(Arc::clone(&p), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("right");
                 interconnectors.push(interconnector);
             }
 
-            let selfconnector = SelfConnector::new("gravity", Arc::clone(&p),Arc::new(gravity));
+            let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&p),Arc::clone(&gravity));
             selfconnectors.push(selfconnector);
             
             nodes.push(p);
         }
-        let interconnector = InterConnector::new(Arc::clone(&bottom_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+        let interconnector = InterConnector::stepped(Arc::clone(&bottom_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("right");
         interconnectors.push(interconnector);
 
-        let selfconnector = SelfConnector::new("gravity", Arc::clone(&bottom_right),Arc::new(gravity));
+        let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&bottom_right),Arc::clone(&gravity));
         selfconnectors.push(selfconnector);
 
         nodes.push(top_left);
@@ -171,110 +199,110 @@ impl Cloth for Mesh<Verlet> {
         Mesh::new(nodes, interconnectors, selfconnectors)
     }
 
-    fn cloth_interweave(height: u8, width: u8, spacing: u8, gravity: i16, spring: f64) -> Mesh<Verlet> {
-        let mut nodes: Vec<SharedNode<Verlet>> = vec!();
-        let mut interconnectors: Vec<InterConnector<Verlet>> = vec!();
-        let mut selfconnectors: Vec<SelfConnector<Verlet>> = vec!();
-        let gravity = move |node: &Node<Verlet>| gravity_constraint(node, 0.001,gravity);
-        let wind = move |node: &Node<Verlet>| wind_constraint(node, -3.0,-0.00);
-        let cloth_constraint = move |node1: &Node<Verlet>, node2: &Node<Verlet>| internode_constraint(node1,node2,spacing.clone() as f64, spring);
+    fn cloth_interweave(height: u8, width: u8, spacing: u8, gravity: i16, spring: f64, seed: u64, damping: Damping) -> Mesh<V> {
+        let mut nodes: Vec<SharedNode<V>> = vec!();
+        let mut interconnectors: Vec<InterConnector<V>> = vec!();
+        let mut selfconnectors: Vec<SelfConnector<V>> = vec!();
+        let gravity: Arc<dyn SelfStepResolve<V>> = Arc::new(Constraint::Gravity { gravity: builder_gravity(gravity, 0.001), delta: None });
+        let wind: Arc<dyn SelfStepResolve<V>> = Arc::new(builder_wind(-3.0, seed));
+        let cloth_constraint: Arc<dyn InterStepResolve<V>> = Arc::new(Constraint::Distance { spacing: spacing as f64, spring, tear: None });
 
-        let top_left = Arc::new(RwLock::new(Node::new(Verlet::new_pinned(f64::from(0),f64::from(0)))));
-        let top_right = Arc::new(RwLock::new(Node::new(Verlet::new_pinned(f64::from(width*spacing),f64::from(0)))));
-        let bottom_left = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(0),f64::from(spacing*height)))));
-        let bottom_right = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(spacing*width),f64::from(spacing*height)))));
+        let top_left = Arc::new(RwLock::new(Node::new(V::new_pinned_at(V::Point::planar(f64::from(0),f64::from(0))))));
+        let top_right = Arc::new(RwLock::new(Node::new(V::new_pinned_at(V::Point::planar(f64::from(width*spacing),f64::from(0))))));
+        let bottom_left = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(0),f64::from(spacing*height))))));
+        let bottom_right = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(spacing*width),f64::from(spacing*height))))));
 
         for x in 1..width {
-            let p_up = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(x*spacing),f64::from(0)))));
+            let p_up = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(x*spacing),f64::from(0))))));
             if x == 1 {
-                let interconnector = InterConnector::new(Arc::clone(&p_up), Some(vec!(Arc::clone(&top_left))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p_up), Some(vec!(Arc::clone(&top_left))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("top");
                 interconnectors.push(interconnector);
             } else {
-                let interconnector = InterConnector::new(Arc::clone(&p_up), Some(vec!(Arc::clone(&nodes[(nodes.len() - 2) as usize]))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p_up), Some(vec!(Arc::clone(&nodes[(nodes.len() - 2) as usize]))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("top");
                 interconnectors.push(interconnector);
             }
 
-            let selfconnector = SelfConnector::new("gravity", Arc::clone(&p_up),Arc::new(gravity));
+            let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&p_up),Arc::clone(&gravity));
             selfconnectors.push(selfconnector);
             
-            let p_down = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(x*spacing),f64::from(height*spacing)))));
+            let p_down = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(x*spacing),f64::from(height*spacing))))));
             if x == 1 {
-                let interconnector = InterConnector::new(Arc::clone(&p_down), Some(vec!(Arc::clone(&bottom_left))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p_down), Some(vec!(Arc::clone(&bottom_left))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("bottom");
                 interconnectors.push(interconnector);
             } else {
-                let interconnector = InterConnector::new(Arc::clone(&p_down), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p_down), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("bottom");
                 interconnectors.push(interconnector);
             }
 
-            let selfconnector = SelfConnector::new("gravity", Arc::clone(&p_down),Arc::new(gravity));
+            let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&p_down),Arc::clone(&gravity));
             selfconnectors.push(selfconnector);
-            let selfconnector = SelfConnector::new("wind", Arc::clone(&p_down),Arc::new(wind));
+            let selfconnector = SelfConnector::stepped("wind", Arc::clone(&p_down),Arc::clone(&wind));
             selfconnectors.push(selfconnector);
 
-            let height_constraint = move |node1: &Node<Verlet>, node2: &Node<Verlet>| internode_constraint(node1,node2,(spacing*height) as f64, spring);
-            let interconnector = InterConnector::new(Arc::clone(&p_down), Some(vec!(Arc::clone(&p_up))),Arc::new(height_constraint));
+            let height_constraint = Constraint::Distance { spacing: (spacing*height) as f64, spring, tear: None };
+            let interconnector = InterConnector::stepped(Arc::clone(&p_down), Some(vec!(Arc::clone(&p_up))),Arc::new(height_constraint)).with_name("height").with_tag("span");
             interconnectors.push(interconnector);
             
             nodes.push(p_up);
             nodes.push(p_down);
         }
-        let interconnector = InterConnector::new(Arc::clone(&bottom_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+        let interconnector = InterConnector::stepped(Arc::clone(&bottom_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("bottom");
         interconnectors.push(interconnector);
 
-        let interconnector = InterConnector::new(Arc::clone(&top_right), Some(vec!(Arc::clone(&nodes[(nodes.len() - 2) as usize]))),Arc::new(cloth_constraint));
+        let interconnector = InterConnector::stepped(Arc::clone(&top_right), Some(vec!(Arc::clone(&nodes[(nodes.len() - 2) as usize]))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("top");
         interconnectors.push(interconnector);
 
-        //let height_constraint = move |node1: &Node<Verlet>, node2: &Node<Verlet>| internode_constraint(node1,node2,(spacing*height) as f64, spring);
+        //let height_constraint = move |node1: &Node<V>, node2: &Node<V>| internode_constraint(node1,node2,(spacing*height) as f64, spring);
         //let interconnector = InterConnector::new(Arc::clone(&bottom_right), Some(vec!(Arc::clone(&top_right))),Arc::new(height_constraint));
         //interconnectors.push(interconnector);
         //let interconnector = InterConnector::new(Arc::clone(&bottom_left), Some(vec!(Arc::clone(&top_left))),Arc::new(height_constraint));
         //interconnectors.push(interconnector);
 
-        let selfconnector = SelfConnector::new("gravity", Arc::clone(&bottom_right),Arc::new(gravity));
+        let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&bottom_right),Arc::clone(&gravity));
         selfconnectors.push(selfconnector);
-        let selfconnector = SelfConnector::new("wind", Arc::clone(&bottom_right),Arc::new(wind));
+        let selfconnector = SelfConnector::stepped("wind", Arc::clone(&bottom_right),Arc::clone(&wind));
         selfconnectors.push(selfconnector);
         
 
 
         for y in 1..height {
-            let p_left = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(0),f64::from(y*spacing)))));
+            let p_left = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(0),f64::from(y*spacing))))));
             if y == 1 {
-                let interconnector = InterConnector::new(Arc::clone(&p_left), Some(vec!(Arc::clone(&top_left))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p_left), Some(vec!(Arc::clone(&top_left))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("left");
                 interconnectors.push(interconnector);
             } else {
-                let interconnector = InterConnector::new(Arc::clone(&p_left), Some(vec!(Arc::clone(&nodes[(nodes.len() - 2) as usize]))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p_left), Some(vec!(Arc::clone(&nodes[(nodes.len() - 2) as usize]))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("left");
                 interconnectors.push(interconnector);
             }
 
-            let selfconnector = SelfConnector::new("gravity", Arc::clone(&p_left),Arc::new(gravity));
+            let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&p_left),Arc::clone(&gravity));
             selfconnectors.push(selfconnector);
             
-            let p_right = Arc::new(RwLock::new(Node::new(Verlet::new(f64::from(width*spacing),f64::from(y*spacing)))));
+            let p_right = Arc::new(RwLock::new(Node::new(V::new_at(V::Point::planar(f64::from(width*spacing),f64::from(y*spacing))))));
             if y == 1 {
-                let interconnector = InterConnector::new(Arc::clone(&p_right), Some(vec!(Arc::clone(&top_right))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p_right), Some(vec!(Arc::clone(&top_right))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("right");
                 interconnectors.push(interconnector);
             } else {
-                let interconnector = InterConnector::new(Arc::clone(&p_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+                let interconnector = InterConnector::stepped(Arc::clone(&p_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("right");
                 interconnectors.push(interconnector);
             }
 
-            let selfconnector = SelfConnector::new("gravity", Arc::clone(&p_right),Arc::new(gravity));
+            let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&p_right),Arc::clone(&gravity));
             selfconnectors.push(selfconnector);
             
-            let width_constraint = move |node1: &Node<Verlet>, node2: &Node<Verlet>| internode_constraint(node1,node2,(spacing*width) as f64, spring);
-            let interconnector = InterConnector::new(Arc::clone(&p_left), Some(vec!(Arc::clone(&p_right))),Arc::new(width_constraint));
+            let width_constraint = Constraint::Distance { spacing: (spacing*width) as f64, spring, tear: None };
+            let interconnector = InterConnector::stepped(Arc::clone(&p_left), Some(vec!(Arc::clone(&p_right))),Arc::new(width_constraint)).with_name("width").with_tag("span");
             interconnectors.push(interconnector);
 
             nodes.push(p_left);
             nodes.push(p_right);
         }
-        let interconnector = InterConnector::new(Arc::clone(&bottom_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::new(cloth_constraint));
+        let interconnector = InterConnector::stepped(Arc::clone(&bottom_right), Some(vec!(Arc::clone(nodes.last().unwrap()))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("right");
         interconnectors.push(interconnector);
-        let interconnector = InterConnector::new(Arc::clone(&bottom_left), Some(vec!(Arc::clone(&nodes[(nodes.len() - 2) as usize]))),Arc::new(cloth_constraint));
+        let interconnector = InterConnector::stepped(Arc::clone(&bottom_left), Some(vec!(Arc::clone(&nodes[(nodes.len() - 2) as usize]))),Arc::clone(&cloth_constraint)).with_name("cloth").with_tag("left");
         interconnectors.push(interconnector);
 
-        let selfconnector = SelfConnector::new("gravity", Arc::clone(&bottom_left),Arc::new(gravity));
+        let selfconnector = SelfConnector::stepped("gravity", Arc::clone(&bottom_left),Arc::clone(&gravity));
         selfconnectors.push(selfconnector);
 
         nodes.push(top_left);
@@ -282,237 +310,1007 @@ impl Cloth for Mesh<Verlet> {
         nodes.push(top_right); 
         nodes.push(bottom_left);
 
-        Mesh::new(nodes, interconnectors, selfconnectors)
+        let mut mesh = Mesh::new(nodes, interconnectors, selfconnectors);
+        mesh.set_damping(damping);
+        mesh
     }
 }
 
 
+// seed for the presets whose C constructors take none, so their wind still replays
+const DEFAULT_SEED: u64 = 0;
+
+// a non-positive delta follows the timestep the mesh is updated with
+fn step_delta(delta: f64) -> Option<f64> {
+    if delta > 0.0 {
+        Some(delta)
+    } else {
+        None
+    }
+}
+
+// Every function below reports through error.rs: null pointers, out of range indices, stale
+// handles and poisoned nodes set the thread's last error and return a zero, null or default
+// value (or the status itself where there is nothing else to return) instead of panicking.
+
+unsafe fn c_string(ptr: *const c_char, name: &'static str) -> Result<String, NacError> {
+    Ok(CStr::from_ptr(non_null(ptr, name)?).to_string_lossy().into_owned())
+}
+
+fn interconnector_index(mesh: &Mesh<Verlet3>, handle: InterHandle) -> Result<usize, NacError> {
+    mesh.interconnector_index(handle).ok_or(NacError::StaleHandle)
+}
+
+fn selfconnector_index(mesh: &Mesh<Verlet3>, handle: SelfHandle) -> Result<usize, NacError> {
+    mesh.selfconnector_index(handle).ok_or(NacError::StaleHandle)
+}
+
+// nodes in the z = 0 plane, as the two dimensional API made them
+#[no_mangle]
+pub unsafe extern fn create_verlet_node(x: f64, y: f64) -> *mut SharedNode<Verlet3> {
+    create_verlet_node3(x, y, 0.0)
+}
+
 #[no_mangle]
-pub unsafe extern fn create_verlet_node(x: f64, y: f64) -> *mut SharedNode<Verlet> {
-    Box::into_raw(Box::new(Arc::new(RwLock::new(Node::new(Verlet::new(x,y))))))
+pub unsafe extern fn create_pinned_verlet_node(x: f64, y: f64) -> *mut SharedNode<Verlet3> {
+    create_pinned_verlet_node3(x, y, 0.0)
 }
 
 #[no_mangle]
-pub unsafe extern fn create_pinned_verlet_node(x: f64, y: f64) -> *mut SharedNode<Verlet> {
-    Box::into_raw(Box::new(Arc::new(RwLock::new(Node::new(Verlet::new_pinned(x,y))))))
+pub unsafe extern fn create_verlet_node3(x: f64, y: f64, z: f64) -> *mut SharedNode<Verlet3> {
+    guarded(std::ptr::null_mut(), ||
+        Ok(Box::into_raw(Box::new(Arc::new(RwLock::new(Node::new(Verlet3::new(x,y,z))))))))
 }
 
 #[no_mangle]
-pub unsafe extern fn free_node(node_ptr: *mut SharedNode<Verlet>) {
-    if !node_ptr.is_null() {
-        let node = node_ptr as *mut SharedNode<Verlet>;
-        Box::from_raw(node);
+pub unsafe extern fn create_pinned_verlet_node3(x: f64, y: f64, z: f64) -> *mut SharedNode<Verlet3> {
+    guarded(std::ptr::null_mut(), ||
+        Ok(Box::into_raw(Box::new(Arc::new(RwLock::new(Node::new(Verlet3::new_pinned(x,y,z))))))))
+}
+
+fn positive_mass(mass: f64) -> Result<f64, NacError> {
+    if mass > 0.0 && mass.is_finite() {
+        Ok(mass)
+    } else {
+        Err(NacError::InvalidArgument(format!("node mass must be positive and finite, got {}", mass)))
     }
 }
 
+// Nodes weigh 1 until given a mass. Pinned nodes keep theirs but act as infinitely heavy.
+#[no_mangle]
+pub unsafe extern fn set_verlet_node_mass(node_ptr: *mut SharedNode<Verlet3>, mass: f64) -> NacStatus {
+    status(|| {
+        let mass = positive_mass(mass)?;
+        write_node(non_null(node_ptr, "node")?)?.data.mass = mass;
+        Ok(())
+    })
+}
+
 #[no_mangle]
-pub unsafe extern fn add_node_to_mesh(mesh_ptr: *mut Mesh<Verlet>, node_ptr: *mut SharedNode<Verlet>) {
-    if !mesh_ptr.is_null() & !node_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        let node = node_ptr as *mut SharedNode<Verlet>;
+pub unsafe extern fn set_mesh_node_mass(mesh_ptr: *mut Mesh<Verlet3>, handle: NodeHandle, mass: f64) -> NacStatus {
+    status(|| {
+        let mass = positive_mass(mass)?;
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        write_node(mesh.node(handle).ok_or(NacError::StaleHandle)?)?.data.mass = mass;
+        Ok(())
+    })
+}
 
-        (*mesh).nodes.push(Arc::clone(&(*node)));
+fn non_negative(value: f64, what: &str) -> Result<f64, NacError> {
+    if value >= 0.0 && value.is_finite() {
+        Ok(value)
+    } else {
+        Err(NacError::InvalidArgument(format!("{} must be positive or 0, got {}", what, value)))
     }
 }
 
+fn mesh_damping(damping: f64, drag: f64) -> Result<Damping, NacError> {
+    Ok(Damping::new(non_negative(damping, "damping")?, non_negative(drag, "drag")?))
+}
+
+// Linear damping is the rate, per second, velocity decays at, and adds to the mesh's.
+// Nodes have none of their own until given some.
+#[no_mangle]
+pub unsafe extern fn set_verlet_node_damping(node_ptr: *mut SharedNode<Verlet3>, damping: f64) -> NacStatus {
+    status(|| {
+        let damping = non_negative(damping, "damping")?;
+        write_node(non_null(node_ptr, "node")?)?.data.damping = damping;
+        Ok(())
+    })
+}
+
 #[no_mangle]
-pub unsafe extern fn add_gravity(mesh_ptr: *mut Mesh<Verlet>, node_ptr: *mut SharedNode<Verlet>, name: *const c_char, delta: f64, gravity: i16) {
-    if !mesh_ptr.is_null() & !node_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        let node = node_ptr as *mut SharedNode<Verlet>;
+pub unsafe extern fn set_mesh_node_damping(mesh_ptr: *mut Mesh<Verlet3>, handle: NodeHandle, damping: f64) -> NacStatus {
+    status(|| {
+        let damping = non_negative(damping, "damping")?;
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        write_node(mesh.node(handle).ok_or(NacError::StaleHandle)?)?.data.damping = damping;
+        Ok(())
+    })
+}
 
-        let constraint = move |node: &Node<Verlet>| gravity_constraint(node, delta, gravity);
+// damping and drag for every node of the mesh, see damping::Damping; 0 for both turns them off
+#[no_mangle]
+pub unsafe extern fn set_cloth_mesh_damping(mesh_ptr: *mut Mesh<Verlet3>, damping: f64, drag: f64) -> NacStatus {
+    status(|| {
+        let damping = mesh_damping(damping, drag)?;
+        non_null_mut(mesh_ptr, "mesh")?.set_damping(damping);
+        Ok(())
+    })
+}
 
-        let name = name as *const c_char;
-        let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+// 0 for stale handles
+#[no_mangle]
+pub unsafe extern fn mesh_node_mass(mesh_ptr: *mut Mesh<Verlet3>, handle: NodeHandle) -> f64 {
+    guarded(0.0, || {
+        let mesh = non_null(mesh_ptr, "mesh")?;
+        Ok(read_node(mesh.node(handle).ok_or(NacError::StaleHandle)?)?.data.mass)
+    })
+}
 
-        (*mesh).selfconnectors.push(SelfConnector::new(&name, Arc::clone(&(*node)), Arc::new(constraint)));
-    }
+#[no_mangle]
+pub unsafe extern fn free_node(node_ptr: *mut SharedNode<Verlet3>) {
+    guarded((), || {
+        if !node_ptr.is_null() {
+            drop(Box::from_raw(node_ptr));
+        }
+        Ok(())
+    })
 }
 
 #[no_mangle]
-pub unsafe extern fn add_bound_gravity(mesh_ptr: *mut Mesh<Verlet>, node_ptr: *mut SharedNode<Verlet>, name: *const c_char, delta: f64, gravity: i16, boundary: f64) {
-    if !mesh_ptr.is_null() & !node_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        let node = node_ptr as *mut SharedNode<Verlet>;
+pub unsafe extern fn add_node_to_mesh(mesh_ptr: *mut Mesh<Verlet3>, node_ptr: *mut SharedNode<Verlet3>) -> NodeHandle {
+    guarded(NodeHandle::default(), || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let node = non_null(node_ptr, "node")?;
 
-        let constraint = move |node: &Node<Verlet>| ground_bound_gravity_constraint(node, delta, gravity, boundary);
+        Ok(mesh.add_node(Arc::clone(node)))
+    })
+}
 
-        let name = name as *const c_char;
-        let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+unsafe fn add_self_constraint(mesh_ptr: *mut Mesh<Verlet3>, node_ptr: *mut SharedNode<Verlet3>, name: *const c_char, constraint: Constraint) -> SelfHandle {
+    guarded(SelfHandle::default(), || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let node = non_null(node_ptr, "node")?;
+        let name = c_string(name, "name")?;
 
-        (*mesh).selfconnectors.push(SelfConnector::new(&name, Arc::clone(&(*node)), Arc::new(constraint)));
-    }
+        Ok(mesh.add_selfconnector(SelfConnector::stepped(&name, Arc::clone(node), Arc::new(constraint))))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn add_gravity(mesh_ptr: *mut Mesh<Verlet3>, node_ptr: *mut SharedNode<Verlet3>, name: *const c_char, delta: f64, gravity: f64) -> SelfHandle {
+    add_self_constraint(mesh_ptr, node_ptr, name, Constraint::Gravity { gravity, delta: step_delta(delta) })
 }
 
 #[no_mangle]
-pub unsafe extern fn add_ground_boundary(mesh_ptr: *mut Mesh<Verlet>, node_ptr: *mut SharedNode<Verlet>, name: *const c_char, boundary: f64) {
-    if !mesh_ptr.is_null() & !node_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        let node = node_ptr as *mut SharedNode<Verlet>;
+pub unsafe extern fn add_bound_gravity(mesh_ptr: *mut Mesh<Verlet3>, node_ptr: *mut SharedNode<Verlet3>, name: *const c_char, delta: f64, gravity: f64, boundary: f64) -> SelfHandle {
+    add_self_constraint(mesh_ptr, node_ptr, name, Constraint::BoundGravity { gravity, boundary, delta: step_delta(delta) })
+}
 
+#[no_mangle]
+pub unsafe extern fn add_ground_boundary(mesh_ptr: *mut Mesh<Verlet3>, node_ptr: *mut SharedNode<Verlet3>, name: *const c_char, boundary: f64) -> SelfHandle {
+    add_self_constraint(mesh_ptr, node_ptr, name, Constraint::GroundBoundary { boundary })
+}
 
-        let constraint = move |node: &Node<Verlet>| ground_boundary_constraint(node, boundary);
 
-        let name = name as *const c_char;
-        let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+#[no_mangle]
+pub unsafe extern fn add_impetus(mesh_ptr: *mut Mesh<Verlet3>, node_ptr: *mut SharedNode<Verlet3>, name: *const c_char, delta: f64, x_force: f64, y_force: f64) -> SelfHandle {
+    add_self_constraint(mesh_ptr, node_ptr, name, Constraint::Force { horz_strength: x_force, vert_strength: y_force, delta: step_delta(delta) })
+}
 
-        (*mesh).selfconnectors.push(SelfConnector::new(&name, Arc::clone(&(*node)), Arc::new(constraint)));
-    }
+#[no_mangle]
+pub unsafe extern fn add_wind(mesh_ptr: *mut Mesh<Verlet3>, node_ptr: *mut SharedNode<Verlet3>, name: *const c_char, x_force: f64, y_force: f64, seed: u64) -> SelfHandle {
+    add_self_constraint(mesh_ptr, node_ptr, name, Constraint::wind(x_force, y_force, seed))
 }
 
+// forces here are accelerations applied over the dt the mesh is updated with
+#[no_mangle]
+pub unsafe extern fn add_accelerated_wind(mesh_ptr: *mut Mesh<Verlet3>, node_ptr: *mut SharedNode<Verlet3>, name: *const c_char, x_force: f64, y_force: f64, seed: u64) -> SelfHandle {
+    add_self_constraint(mesh_ptr, node_ptr, name, Constraint::accelerated_wind(x_force, y_force, seed))
+}
 
+// A node group collects nodes for the multi-node connectors; the connectors keep their own
+// references, so the group can be freed or reused once they are added.
 #[no_mangle]
-pub unsafe extern fn add_impetus(mesh_ptr: *mut Mesh<Verlet>, node_ptr: *mut SharedNode<Verlet>, name: *const c_char, delta: f64, x_force: f64, y_force: f64) {
-    if !mesh_ptr.is_null() & !node_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        let node = node_ptr as *mut SharedNode<Verlet>;
+pub unsafe extern fn init_node_group() -> *mut SharedNodes<Verlet3> {
+    guarded(std::ptr::null_mut(), || Ok(Box::into_raw(Box::new(vec!()))))
+}
 
-        let constraint = move |node: &Node<Verlet>| force_constraint(node, delta, x_force, y_force);
+#[no_mangle]
+pub unsafe extern fn add_to_node_group(node_group_ptr: *mut SharedNodes<Verlet3>, node_ptr: *mut SharedNode<Verlet3>) -> NacStatus {
+    status(|| {
+        let node_group = non_null_mut(node_group_ptr, "node_group")?;
+        let node = non_null(node_ptr, "node")?;
+
+        node_group.push(Arc::clone(node));
+        Ok(())
+    })
+}
 
-        let name = name as *const c_char;
-        let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+#[no_mangle]
+pub unsafe extern fn node_group_len(node_group_ptr: *mut SharedNodes<Verlet3>) -> usize {
+    guarded(0, || Ok(non_null(node_group_ptr, "node_group")?.len()))
+}
 
-        (*mesh).selfconnectors.push(SelfConnector::new(&name, Arc::clone(&(*node)), Arc::new(constraint)));
-    }
+#[no_mangle]
+pub unsafe extern fn free_node_group(node_group_ptr: *mut SharedNodes<Verlet3>) {
+    guarded((), || {
+        if !node_group_ptr.is_null() {
+            drop(Box::from_raw(node_group_ptr));
+        }
+        Ok(())
+    })
+}
+
+unsafe fn add_group_connector(mesh_ptr: *mut Mesh<Verlet3>, primary_node_ptr: *mut SharedNode<Verlet3>, node_group_ptr: *mut SharedNodes<Verlet3>, constraint: Constraint) -> InterHandle {
+    guarded(InterHandle::default(), || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let primary_node = non_null(primary_node_ptr, "primary_node")?;
+        let node_group = non_null(node_group_ptr, "node_group")?;
+
+        Ok(mesh.add_interconnector(InterConnector::group(Arc::clone(primary_node), node_group.clone(), Arc::new(constraint))))
+    })
 }
 
+// keeps every node of the group `distance` from the primary node
 #[no_mangle]
-pub unsafe extern fn add_wind(mesh_ptr: *mut Mesh<Verlet>, node_ptr: *mut SharedNode<Verlet>, name: *const c_char, x_force: f64, y_force: f64) {
-    if !mesh_ptr.is_null() & !node_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        let node = node_ptr as *mut SharedNode<Verlet>;
+pub unsafe extern fn add_multiconnector(mesh_ptr: *mut Mesh<Verlet3>, primary_node_ptr: *mut SharedNode<Verlet3>, node_group_ptr: *mut SharedNodes<Verlet3>, distance: f64, spring: f64) -> InterHandle {
+    add_group_connector(mesh_ptr, primary_node_ptr, node_group_ptr, Constraint::Distance { spacing: distance, spring, tear: None })
+}
 
-        let constraint = move |node: &Node<Verlet>| wind_constraint(node, x_force, y_force);
+// angle in radians at the primary node between the first two nodes of the group
+#[no_mangle]
+pub unsafe extern fn add_angle_connector(mesh_ptr: *mut Mesh<Verlet3>, vertex_node_ptr: *mut SharedNode<Verlet3>, node_group_ptr: *mut SharedNodes<Verlet3>, angle: f64, stiffness: f64) -> InterHandle {
+    add_group_connector(mesh_ptr, vertex_node_ptr, node_group_ptr, Constraint::Angle { angle, stiffness })
+}
 
-        let name = name as *const c_char;
-        let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+// area of the polygon through the primary node and then the group in order
+#[no_mangle]
+pub unsafe extern fn add_area_connector(mesh_ptr: *mut Mesh<Verlet3>, primary_node_ptr: *mut SharedNode<Verlet3>, node_group_ptr: *mut SharedNodes<Verlet3>, area: f64, stiffness: f64) -> InterHandle {
+    add_group_connector(mesh_ptr, primary_node_ptr, node_group_ptr, Constraint::Area { area, stiffness })
+}
 
-        (*mesh).selfconnectors.push(SelfConnector::new(&name, Arc::clone(&(*node)), Arc::new(constraint)));
+#[no_mangle]
+pub unsafe extern fn add_centroid_connector(mesh_ptr: *mut Mesh<Verlet3>, primary_node_ptr: *mut SharedNode<Verlet3>, node_group_ptr: *mut SharedNodes<Verlet3>, x: f64, y: f64, stiffness: f64) -> InterHandle {
+    add_group_connector(mesh_ptr, primary_node_ptr, node_group_ptr, Constraint::Centroid { x, y, stiffness })
+}
+
+#[no_mangle]
+pub unsafe extern fn add_connector(mesh_ptr: *mut Mesh<Verlet3>, primary_node_ptr: *mut SharedNode<Verlet3>, secondary_node_ptr: *mut SharedNode<Verlet3>, dist: f64, spring: f64) -> InterHandle {
+    guarded(InterHandle::default(), || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let primary_node = non_null(primary_node_ptr, "primary_node")?;
+        let secondary_node = non_null(secondary_node_ptr, "secondary_node")?;
+
+        let constraint = Constraint::Distance { spacing: dist, spring, tear: None };
+
+        Ok(mesh.add_interconnector(InterConnector::stepped(Arc::clone(primary_node), Some(vec!(Arc::clone(secondary_node))), Arc::new(constraint))))
+    })
+}
+
+impl PartialEq for SelfConnector<Verlet3> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
     }
 }
 
-/*#[no_mangle]
-pub unsafe extern init_node_group() -> *mut Option<SharedNodes<Verlet>> {
-    None
+#[no_mangle]
+pub unsafe extern fn remove_constraint_by_id(mesh: *mut Mesh<Verlet3>, id: *const c_char) -> NacStatus {
+    status(|| {
+        let mesh = non_null_mut(mesh, "mesh")?;
+        let name = c_string(id, "id")?;
+
+        mesh.remove_selfconnectors_by_name(&name);
+        Ok(())
+    })
 }
 
 #[no_mangle]
-pub unsafe extern add_to_node_group(node_group: *mut Option<SharedNodes<Verlet>>, node: *mut SharedNode<Verlet>) {
-    if !node_group.is_null() & !node.is_null() {
-        let node_group = node_group as *mut Option<SharedNodes<Verlet>>;
-        let node = node as *mut SharedNode<Verlet>;
+pub unsafe extern fn remove_interconnectors_by_name(mesh_ptr: *mut Mesh<Verlet3>, name: *const c_char) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        Ok(mesh.remove_interconnectors_by_name(&c_string(name, "name")?))
+    })
+}
 
-        if let Some(nodes) = node_group {
-            nodes.push(Arc::clone(&(*node)));
-        } else {
-            nodes = vec!(Arc::clone(&(*node)));
-        }
-    } 
+#[no_mangle]
+pub unsafe extern fn remove_interconnectors_by_tag(mesh_ptr: *mut Mesh<Verlet3>, tag: *const c_char) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        Ok(mesh.remove_interconnectors_by_tag(&c_string(tag, "tag")?))
+    })
 }
 
 #[no_mangle]
-pub unsafe extern fn add_multiconnector(mesh_ptr: *mut Mesh<Verlet>, primary_node_ptr: *mut SharedNode<Verlet>, node_group_ptr: *mut Option<SharedNodes<Verlet>>, distance: f64, spring: f64) {
-    if !mesh_ptr.is_null() & !primary_node_ptr.is_null() & !node_group_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        let primary_node = primary_node_ptr as *mut SharedNode<Verlet>;
-        let node_group = node_group_ptr as *mut Option<SharedNodes<Verlet>>;
+pub unsafe extern fn set_interconnectors_enabled_by_name(mesh_ptr: *mut Mesh<Verlet3>, name: *const c_char, enabled: bool) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        Ok(mesh.set_interconnectors_enabled_by_name(&c_string(name, "name")?, enabled))
+    })
+}
 
-        let constraint = move |node1: &Node<Verlet>, node2: &Node<Verlet>| internode_constraint(node1,node2,distance,spring);
+#[no_mangle]
+pub unsafe extern fn set_interconnectors_enabled_by_tag(mesh_ptr: *mut Mesh<Verlet3>, tag: *const c_char, enabled: bool) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        Ok(mesh.set_interconnectors_enabled_by_tag(&c_string(tag, "tag")?, enabled))
+    })
+}
 
-        (*mesh).interconnectors.push(InterConnector::new(Arc::clone(&(*primary_node)), (*node_group), Arc::new(constraint)));
-    }               
-}*/
+#[no_mangle]
+pub unsafe extern fn set_interconnector_name(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle, name: *const c_char) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let name = c_string(name, "name")?;
+        let idx = interconnector_index(mesh, handle)?;
+        mesh.interconnectors[idx].name = name;
+        Ok(true)
+    })
+}
 
 #[no_mangle]
-pub unsafe extern fn add_connector(mesh_ptr: *mut Mesh<Verlet>, primary_node_ptr: *mut SharedNode<Verlet>, secondary_node_ptr: *mut SharedNode<Verlet>, dist: f64, spring: f64) {
-    if !mesh_ptr.is_null() & !primary_node_ptr.is_null() & !secondary_node_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        let primary_node = primary_node_ptr as *mut SharedNode<Verlet>;
-        let secondary_node = secondary_node_ptr as *mut SharedNode<Verlet>;
+pub unsafe extern fn add_interconnector_tag(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle, tag: *const c_char) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let tag = c_string(tag, "tag")?;
+        let idx = interconnector_index(mesh, handle)?;
+        mesh.interconnectors[idx].tags.insert(tag);
+        Ok(true)
+    })
+}
 
-        //let dist: f64;
-        ////if let (Ok(prim_node), Ok(second_node)) = (primary_node.read(), secondary_node.read()) {
-        //let (prim_node,second_node) = (primary_node.read(), secondary_node.read());
-        //let diff_x = prim_node.read().unwrap().data.position.x - second_node.read().unwrap().data.position.x;
-        //let diff_y = prim_node.read().unwrap().data.position.y - second_node.read().unwrap().data.position.y;
-        //dist = (diff_x.powi(2) + diff_y.powi(2)).sqrt() / 2.0;
-        ////println!("x1: {:?}, x2: {:?}, diff:{:?}", prim_node.read().unwrap().data.position.x, second_node.read().unwrap().data.position.x, dist);
+// Write the handles of matching interconnectors into `handles`, at most `capacity` of them,
+// and return how many matched; a first call with capacity 0 sizes the buffer.
+#[no_mangle]
+pub unsafe extern fn interconnectors_by_name(mesh_ptr: *mut Mesh<Verlet3>, name: *const c_char, handles: *mut InterHandle, capacity: usize) -> usize {
+    guarded(0, || {
+        let mesh = non_null(mesh_ptr, "mesh")?;
+        write_handles(&mesh.interconnectors_by_name(&c_string(name, "name")?), handles, capacity)
+    })
+}
 
-        let constraint = move |node1: &Node<Verlet>, node2: &Node<Verlet>| internode_constraint(node1,node2,dist,spring);
+#[no_mangle]
+pub unsafe extern fn interconnectors_by_tag(mesh_ptr: *mut Mesh<Verlet3>, tag: *const c_char, handles: *mut InterHandle, capacity: usize) -> usize {
+    guarded(0, || {
+        let mesh = non_null(mesh_ptr, "mesh")?;
+        write_handles(&mesh.interconnectors_by_tag(&c_string(tag, "tag")?), handles, capacity)
+    })
+}
 
-        (*mesh).interconnectors.push(InterConnector::new(Arc::clone(&(*primary_node)), Some(vec!(Arc::clone(&(*secondary_node)))), Arc::new(constraint)));
-    } else {
-        panic!("add_connector null pointer!!!");
+unsafe fn write_handles(matched: &[InterHandle], handles: *mut InterHandle, capacity: usize) -> Result<usize, NacError> {
+    let written = matched.len().min(capacity);
+    if written > 0 {
+        std::slice::from_raw_parts_mut(non_null_mut(handles, "handles")?, written).copy_from_slice(&matched[..written]);
     }
+    Ok(matched.len())
 }
 
-impl PartialEq for SelfConnector<Verlet> {
-    fn eq(&self, other: &Self) -> bool {
-        self.name == other.name
+// returned strings are owned by the caller and released with string_free
+#[no_mangle]
+pub unsafe extern fn get_interconnector_name(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle) -> *mut c_char {
+    guarded(std::ptr::null_mut(), || {
+        let mesh = non_null(mesh_ptr, "mesh")?;
+        Ok(into_c_string(&mesh.interconnectors[interconnector_index(mesh, handle)?].name))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn get_interconnector_tag_count(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle) -> usize {
+    guarded(0, || {
+        let mesh = non_null(mesh_ptr, "mesh")?;
+        Ok(mesh.interconnectors[interconnector_index(mesh, handle)?].tags.len())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn get_interconnector_tag(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle, tag_index: usize) -> *mut c_char {
+    guarded(std::ptr::null_mut(), || {
+        let mesh = non_null(mesh_ptr, "mesh")?;
+        let tags = &mesh.interconnectors[interconnector_index(mesh, handle)?].tags;
+        let tag = tags.iter().nth(tag_index).ok_or(NacError::IndexOutOfRange { index: tag_index, len: tags.len() })?;
+        Ok(into_c_string(tag))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn get_interconnector_enabled(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle) -> bool {
+    guarded(false, || {
+        let mesh = non_null(mesh_ptr, "mesh")?;
+        Ok(mesh.interconnectors[interconnector_index(mesh, handle)?].enabled)
+    })
+}
+
+// Setters for the parameters of built-in constraints. Connectors built from closures, or whose
+// constraint has no such parameter, are left alone; the by-name and by-tag forms return how
+// many connectors changed.
+#[no_mangle]
+pub unsafe extern fn set_interconnector_spring(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle, spring: f64) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        interconnector_index(mesh, handle)?;
+        Ok(mesh.edit_interconnector(handle, |constraint: &mut Constraint| constraint.set_spring(spring))
+            || mesh.edit_group_interconnector(handle, |constraint: &mut Constraint| constraint.set_spring(spring)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_interconnector_spacing(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle, spacing: f64) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        interconnector_index(mesh, handle)?;
+        Ok(mesh.edit_interconnector(handle, |constraint: &mut Constraint| constraint.set_spacing(spacing))
+            || mesh.edit_group_interconnector(handle, |constraint: &mut Constraint| constraint.set_spacing(spacing)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_interconnectors_spring_by_name(mesh_ptr: *mut Mesh<Verlet3>, name: *const c_char, spring: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let name = c_string(name, "name")?;
+        Ok(mesh.edit_interconnectors_by_name(&name, |constraint: &mut Constraint| constraint.set_spring(spring))
+            + mesh.edit_group_interconnectors_by_name(&name, |constraint: &mut Constraint| constraint.set_spring(spring)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_interconnectors_spring_by_tag(mesh_ptr: *mut Mesh<Verlet3>, tag: *const c_char, spring: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let tag = c_string(tag, "tag")?;
+        Ok(mesh.edit_interconnectors_by_tag(&tag, |constraint: &mut Constraint| constraint.set_spring(spring))
+            + mesh.edit_group_interconnectors_by_tag(&tag, |constraint: &mut Constraint| constraint.set_spring(spring)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_interconnectors_spacing_by_name(mesh_ptr: *mut Mesh<Verlet3>, name: *const c_char, spacing: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let name = c_string(name, "name")?;
+        Ok(mesh.edit_interconnectors_by_name(&name, |constraint: &mut Constraint| constraint.set_spacing(spacing))
+            + mesh.edit_group_interconnectors_by_name(&name, |constraint: &mut Constraint| constraint.set_spacing(spacing)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_interconnectors_spacing_by_tag(mesh_ptr: *mut Mesh<Verlet3>, tag: *const c_char, spacing: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let tag = c_string(tag, "tag")?;
+        Ok(mesh.edit_interconnectors_by_tag(&tag, |constraint: &mut Constraint| constraint.set_spacing(spacing))
+            + mesh.edit_group_interconnectors_by_tag(&tag, |constraint: &mut Constraint| constraint.set_spacing(spacing)))
+    })
+}
+
+// Tear thresholds of distance connectors: kind 0 never tears, 1 tears past `threshold` times
+// the spacing, 2 past a distance of `threshold`
+fn tear(kind: u8, threshold: f64) -> Result<Option<Tear>, NacError> {
+    match kind {
+        0 => Ok(None),
+        1 => Ok(Some(Tear::Ratio(threshold))),
+        2 => Ok(Some(Tear::Distance(threshold))),
+        _ => Err(NacError::InvalidArgument(format!("no tear of kind {}", kind))),
     }
 }
 
 #[no_mangle]
-pub unsafe extern fn remove_constraint_by_id(mesh: *mut Mesh<Verlet>, id: *const c_char) {
-    if !mesh.is_null() & !id.is_null() {
-        let mesh = mesh as *mut Mesh<Verlet>;
-        let id = id as *const c_char;
-        let name = CStr::from_ptr(id).to_string_lossy().into_owned();
-        //let name = str::from_utf8(CStr::from_ptr(id).to_bytes()).unwrap().to_owned();
+pub unsafe extern fn set_interconnector_tear(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle, kind: u8, threshold: f64) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        interconnector_index(mesh, handle)?;
+        let tear = tear(kind, threshold)?;
+        Ok(mesh.edit_interconnector(handle, |constraint: &mut Constraint| constraint.set_tear(tear))
+            || mesh.edit_group_interconnector(handle, |constraint: &mut Constraint| constraint.set_tear(tear)))
+    })
+}
 
-        //let unmatched_connectors = (*mesh).selfconnectors.iter().filter(|*connector| connector.name != name).collect();
-        //(*mesh).selfconnectors = unmatched_connectors;
-        for (idx, connector) in (*mesh).selfconnectors.iter().enumerate() {
-            if connector.name == name {
-                if idx < (*mesh).selfconnectors.len() {
-                    (*mesh).selfconnectors.remove(idx);
-                }
-            }
+#[no_mangle]
+pub unsafe extern fn set_interconnectors_tear_by_name(mesh_ptr: *mut Mesh<Verlet3>, name: *const c_char, kind: u8, threshold: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let name = c_string(name, "name")?;
+        let tear = tear(kind, threshold)?;
+        Ok(mesh.edit_interconnectors_by_name(&name, |constraint: &mut Constraint| constraint.set_tear(tear))
+            + mesh.edit_group_interconnectors_by_name(&name, |constraint: &mut Constraint| constraint.set_tear(tear)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_interconnectors_tear_by_tag(mesh_ptr: *mut Mesh<Verlet3>, tag: *const c_char, kind: u8, threshold: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let tag = c_string(tag, "tag")?;
+        let tear = tear(kind, threshold)?;
+        Ok(mesh.edit_interconnectors_by_tag(&tag, |constraint: &mut Constraint| constraint.set_tear(tear))
+            + mesh.edit_group_interconnectors_by_tag(&tag, |constraint: &mut Constraint| constraint.set_tear(tear)))
+    })
+}
+
+// stiffness of angle, area and centroid connectors
+#[no_mangle]
+pub unsafe extern fn set_interconnector_stiffness(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle, stiffness: f64) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        interconnector_index(mesh, handle)?;
+        Ok(mesh.edit_group_interconnector(handle, |constraint: &mut Constraint| constraint.set_stiffness(stiffness)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_interconnectors_stiffness_by_name(mesh_ptr: *mut Mesh<Verlet3>, name: *const c_char, stiffness: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let name = c_string(name, "name")?;
+        Ok(mesh.edit_group_interconnectors_by_name(&name, |constraint: &mut Constraint| constraint.set_stiffness(stiffness)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_interconnectors_stiffness_by_tag(mesh_ptr: *mut Mesh<Verlet3>, tag: *const c_char, stiffness: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let tag = c_string(tag, "tag")?;
+        Ok(mesh.edit_group_interconnectors_by_tag(&tag, |constraint: &mut Constraint| constraint.set_stiffness(stiffness)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_selfconnector_gravity(mesh_ptr: *mut Mesh<Verlet3>, handle: SelfHandle, gravity: f64) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        selfconnector_index(mesh, handle)?;
+        Ok(mesh.edit_selfconnector(handle, |constraint: &mut Constraint| constraint.set_gravity(gravity)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_selfconnectors_gravity_by_name(mesh_ptr: *mut Mesh<Verlet3>, name: *const c_char, gravity: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let name = c_string(name, "name")?;
+        Ok(mesh.edit_selfconnectors_by_name(&name, |constraint: &mut Constraint| constraint.set_gravity(gravity)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_selfconnector_boundary(mesh_ptr: *mut Mesh<Verlet3>, handle: SelfHandle, boundary: f64) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        selfconnector_index(mesh, handle)?;
+        Ok(mesh.edit_selfconnector(handle, |constraint: &mut Constraint| constraint.set_boundary(boundary)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_selfconnectors_boundary_by_name(mesh_ptr: *mut Mesh<Verlet3>, name: *const c_char, boundary: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let name = c_string(name, "name")?;
+        Ok(mesh.edit_selfconnectors_by_name(&name, |constraint: &mut Constraint| constraint.set_boundary(boundary)))
+    })
+}
+
+// wind and impetus strengths
+#[no_mangle]
+pub unsafe extern fn set_selfconnector_strength(mesh_ptr: *mut Mesh<Verlet3>, handle: SelfHandle, x_force: f64, y_force: f64) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        selfconnector_index(mesh, handle)?;
+        Ok(mesh.edit_selfconnector(handle, |constraint: &mut Constraint| constraint.set_strength(x_force, y_force)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_selfconnectors_strength_by_name(mesh_ptr: *mut Mesh<Verlet3>, name: *const c_char, x_force: f64, y_force: f64) -> usize {
+    guarded(0, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let name = c_string(name, "name")?;
+        Ok(mesh.edit_selfconnectors_by_name(&name, |constraint: &mut Constraint| constraint.set_strength(x_force, y_force)))
+    })
+}
+
+fn into_c_string(value: &str) -> *mut c_char {
+    // interior nul bytes cannot cross into C, drop them rather than the whole string
+    CString::new(value.replace('\0', "")).unwrap_or_default().into_raw()
+}
+
+#[no_mangle]
+pub unsafe extern fn string_free(string_ptr: *mut c_char) {
+    guarded((), || {
+        if !string_ptr.is_null() {
+            drop(CString::from_raw(string_ptr));
         }
-    }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn mesh_node_handle(mesh_ptr: *mut Mesh<Verlet3>, idx: usize) -> NodeHandle {
+    guarded(NodeHandle::default(), || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        mesh.sync_handles();
+        mesh.node_handle(idx).ok_or(NacError::IndexOutOfRange { index: idx, len: mesh.nodes.len() })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn mesh_interconnector_handle(mesh_ptr: *mut Mesh<Verlet3>, idx: usize) -> InterHandle {
+    guarded(InterHandle::default(), || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        mesh.sync_handles();
+        mesh.interconnector_handle(idx).ok_or(NacError::IndexOutOfRange { index: idx, len: mesh.interconnectors.len() })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn mesh_selfconnector_handle(mesh_ptr: *mut Mesh<Verlet3>, idx: usize) -> SelfHandle {
+    guarded(SelfHandle::default(), || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        mesh.sync_handles();
+        mesh.selfconnector_handle(idx).ok_or(NacError::IndexOutOfRange { index: idx, len: mesh.selfconnectors.len() })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn remove_node_from_mesh(mesh_ptr: *mut Mesh<Verlet3>, handle: NodeHandle) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        mesh.remove_node(handle).map(|_node| true).ok_or(NacError::StaleHandle)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn remove_interconnector_from_mesh(mesh_ptr: *mut Mesh<Verlet3>, handle: InterHandle) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        mesh.remove_interconnector(handle).map(|_connector| true).ok_or(NacError::StaleHandle)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn remove_selfconnector_from_mesh(mesh_ptr: *mut Mesh<Verlet3>, handle: SelfHandle) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        mesh.remove_selfconnector(handle).map(|_connector| true).ok_or(NacError::StaleHandle)
+    })
 }
 
 
 #[no_mangle]
-pub unsafe extern fn get_cloth_mesh_field(h: u8, w: u8, s: u8, g: i16, wind: f64, spring: f64)
-    -> *mut Mesh<Verlet>
+pub unsafe extern fn get_cloth_mesh_field(h: u8, w: u8, s: u8, g: i16, wind: f64, spring: f64, seed: u64, damping: f64, drag: f64)
+    -> *mut Mesh<Verlet3>
 {
-    let mesh = Cloth::new_cloth(w, h, s, g, wind, spring);
-    Box::into_raw(Box::new(mesh)) as *mut Mesh<Verlet>
+    guarded(std::ptr::null_mut(), || {
+        let mesh: Mesh<Verlet3> = Cloth::new_cloth(w, h, s, g, wind, spring, seed, mesh_damping(damping, drag)?);
+        Ok(Box::into_raw(Box::new(mesh)))
+    })
 }
 
 #[no_mangle]
-pub unsafe extern fn empty_cloth() -> *mut Mesh<Verlet> {
-    let mesh = Cloth::empty_cloth();
-    Box::into_raw(Box::new(mesh)) as *mut Mesh<Verlet>
+pub unsafe extern fn empty_cloth() -> *mut Mesh<Verlet3> {
+    guarded(std::ptr::null_mut(), || {
+        let mesh: Mesh<Verlet3> = Cloth::empty_cloth();
+        Ok(Box::into_raw(Box::new(mesh)))
+    })
 }
 
 #[no_mangle]
 pub unsafe extern fn get_cloth_mesh(h: u8, w: u8, s: u8)
-    -> *mut Mesh<Verlet>
+    -> *mut Mesh<Verlet3>
 {
-    let mesh = Cloth::cloth_boundaries(w, h, s);
-    Box::into_raw(Box::new(mesh)) as *mut Mesh<Verlet>
+    guarded(std::ptr::null_mut(), || {
+        let mesh: Mesh<Verlet3> = Cloth::cloth_boundaries(w, h, s, DEFAULT_SEED);
+        Ok(Box::into_raw(Box::new(mesh)))
+    })
 }
 
 #[no_mangle]
-pub unsafe extern fn get_woven_cloth_mesh(h: u8, w: u8, s: u8, g: i16, spring: f64)
-    -> *mut Mesh<Verlet>
+pub unsafe extern fn get_woven_cloth_mesh(h: u8, w: u8, s: u8, g: i16, spring: f64, damping: f64, drag: f64)
+    -> *mut Mesh<Verlet3>
 {
-    let mesh = Cloth::cloth_interweave(w, h, s, g, spring);
-    Box::into_raw(Box::new(mesh)) as *mut Mesh<Verlet>
+    guarded(std::ptr::null_mut(), || {
+        let mesh: Mesh<Verlet3> = Cloth::cloth_interweave(w, h, s, g, spring, DEFAULT_SEED, mesh_damping(damping, drag)?);
+        Ok(Box::into_raw(Box::new(mesh)))
+    })
 }
 
 
+// returns null if the scene file cannot be read or refers to missing nodes
+#[no_mangle]
+pub unsafe extern fn load_mesh_from_file(path: *const c_char) -> *mut Mesh<Verlet3> {
+    guarded(std::ptr::null_mut(), || {
+        let path = c_string(path, "path")?;
+        Ok(Box::into_raw(Box::new(load_mesh(&path)?)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn update_cloth_mesh(mesh_ptr: *mut Mesh<Verlet3>, delta: f64, physics_accuracy: u8) -> NacStatus {
+    status(|| non_null_mut(mesh_ptr, "mesh")?.update(delta, physics_accuracy))
+}
+
+// 0 leaves integration to the gravity constraints, 1 Verlet, 2 semi-implicit Euler, 3 RK4;
+// returns false for any other kind
 #[no_mangle]
-pub unsafe extern fn update_cloth_mesh(mesh_ptr: *mut Mesh<Verlet>, delta: f64, physics_accuracy: u8) {
-    if !mesh_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        (*mesh).update(delta, physics_accuracy);
+pub unsafe extern fn set_cloth_mesh_integrator(mesh_ptr: *mut Mesh<Verlet3>, kind: u8) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let integrator: Arc<dyn Integrator<Verlet3>> = match kind {
+            0 => {
+                mesh.clear_integrator();
+                return Ok(true);
+            },
+            1 => Arc::new(VerletIntegrator),
+            2 => Arc::new(SemiImplicitEuler),
+            3 => Arc::new(Rk4),
+            _ => return Err(NacError::InvalidArgument(format!("no integrator of kind {}", kind))),
+        };
+        mesh.set_integrator(integrator);
+        Ok(true)
+    })
+}
+
+// calls `callback` (see tear::BreakCallback) for every interconnector an update tears out
+#[no_mangle]
+pub unsafe extern fn set_cloth_mesh_break_callback(mesh_ptr: *mut Mesh<Verlet3>, callback: BreakCallback, user_data: *mut c_void) -> NacStatus {
+    status(|| {
+        let target = CBreakCallback::new(callback, user_data);
+        non_null_mut(mesh_ptr, "mesh")?.set_break_callback(move |torn: &Break| target.call(torn));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn clear_cloth_mesh_break_callback(mesh_ptr: *mut Mesh<Verlet3>) -> NacStatus {
+    status(|| {
+        non_null_mut(mesh_ptr, "mesh")?.clear_break_callback();
+        Ok(())
+    })
+}
+
+// Routes the mesh's log to `callback` (see logging::LogCallback), letting through levels up
+// to `max_level`: 3 for warnings, 4 adds connector changes, 5 adds every step.
+#[no_mangle]
+pub unsafe extern fn set_cloth_mesh_log_callback(mesh_ptr: *mut Mesh<Verlet3>, callback: LogCallback, user_data: *mut c_void, max_level: u8) -> NacStatus {
+    status(|| {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let level = Level::from_usize(usize::from(max_level))
+            .ok_or_else(|| NacError::InvalidArgument(format!("no log level {}", max_level)))?;
+        mesh.set_logger(CallbackDrain::new(callback, user_data).logger(level));
+        Ok(())
+    })
+}
+
+// stops the mesh logging, to be called before a callback's user data goes away
+#[no_mangle]
+pub unsafe extern fn clear_cloth_mesh_log_callback(mesh_ptr: *mut Mesh<Verlet3>) -> NacStatus {
+    status(|| {
+        non_null_mut(mesh_ptr, "mesh")?.set_logger(logging::discard());
+        Ok(())
+    })
+}
+
+// warns about interconnectors correcting a node by more than `threshold` in a pass;
+// a negative threshold stops it
+#[no_mangle]
+pub unsafe extern fn set_cloth_mesh_correction_logging(mesh_ptr: *mut Mesh<Verlet3>, threshold: f64) -> NacStatus {
+    status(|| {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        if threshold < 0.0 {
+            mesh.clear_correction_logging();
+        } else {
+            mesh.log_corrections_over(threshold);
+        }
+        Ok(())
+    })
+}
+
+// 0 fails the update on a poisoned node, 1 clears the poison and carries on, 2 quarantines
+// the node until it is released; NacStatus_InvalidArgument for anything else
+#[no_mangle]
+pub unsafe extern fn set_cloth_mesh_poison_policy(mesh_ptr: *mut Mesh<Verlet3>, policy: u8) -> NacStatus {
+    status(|| {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        mesh.set_poison_policy(match policy {
+            0 => PoisonPolicy::Fail,
+            1 => PoisonPolicy::Recover,
+            2 => PoisonPolicy::Quarantine,
+            _ => return Err(NacError::InvalidArgument(format!("no poison policy {}", policy))),
+        });
+        Ok(())
+    })
+}
+
+// nodes recovered or quarantined by updates since the poisonings were last cleared
+#[no_mangle]
+pub unsafe extern fn cloth_mesh_poisoning_count(mesh_ptr: *const Mesh<Verlet3>) -> usize {
+    guarded(0, || Ok(non_null(mesh_ptr, "mesh")?.poisonings().len()))
+}
+
+// a null handle when the node was only reachable through a connector
+#[no_mangle]
+pub unsafe extern fn cloth_mesh_poisoning_node(mesh_ptr: *const Mesh<Verlet3>, idx: usize) -> NodeHandle {
+    guarded(NodeHandle::default(), || Ok(checked(non_null(mesh_ptr, "mesh")?.poisonings(), idx)?.node.unwrap_or_default()))
+}
+
+#[no_mangle]
+pub unsafe extern fn clear_cloth_mesh_poisonings(mesh_ptr: *mut Mesh<Verlet3>) -> NacStatus {
+    status(|| {
+        non_null_mut(mesh_ptr, "mesh")?.take_poisonings();
+        Ok(())
+    })
+}
+
+// lets a quarantined node step again; false if it was not quarantined
+#[no_mangle]
+pub unsafe extern fn release_cloth_mesh_node(mesh_ptr: *mut Mesh<Verlet3>, handle: NodeHandle) -> bool {
+    guarded(false, || Ok(non_null_mut(mesh_ptr, "mesh")?.release_node(handle)))
+}
+
+// releases every quarantined node, returning how many there were
+#[no_mangle]
+pub unsafe extern fn release_cloth_mesh_quarantine(mesh_ptr: *mut Mesh<Verlet3>) -> usize {
+    guarded(0, || Ok(non_null_mut(mesh_ptr, "mesh")?.release_quarantine()))
+}
+
+// iterates until the max (or rms) constraint error left by a pass is within tolerance
+#[no_mangle]
+pub unsafe extern fn update_cloth_mesh_converged(mesh_ptr: *mut Mesh<Verlet3>, delta: f64, tolerance: f64, max_iterations: u8, rms: bool) -> SolveReport {
+    guarded(SolveReport::default(), || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        let norm = if rms { ResidualNorm::Rms } else { ResidualNorm::Max };
+        mesh.update_converged(delta, Convergence { tolerance, max_iterations, norm })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn set_cloth_mesh_fixed_step(mesh_ptr: *mut Mesh<Verlet3>, delta: f64, max_substeps: u32, physics_accuracy: u8) -> NacStatus {
+    status(|| {
+        non_null_mut(mesh_ptr, "mesh")?.set_fixed_step(delta, max_substeps, physics_accuracy);
+        Ok(())
+    })
+}
+
+// runs as many fixed substeps as frame_time covers and returns the interpolation alpha;
+// a frame_time that is not finite runs none and reports InvalidArgument
+#[no_mangle]
+pub unsafe extern fn advance_cloth_mesh(mesh_ptr: *mut Mesh<Verlet3>, frame_time: f64) -> f64 {
+    guarded(0.0, || non_null_mut(mesh_ptr, "mesh")?.advance(frame_time))
+}
+
+// disabling falls back to the serial Gauss-Seidel solver
+#[no_mangle]
+pub unsafe extern fn set_cloth_mesh_jacobi(mesh_ptr: *mut Mesh<Verlet3>, enabled: bool) -> NacStatus {
+    status(|| {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        if enabled {
+            mesh.enable_jacobi();
+        } else {
+            mesh.set_solver(Solver::Serial)?;
+        }
+        Ok(())
+    })
+}
+
+// threads <= 1 switches back to the serial solver
+#[no_mangle]
+pub unsafe extern fn set_mesh_solver_threads(mesh_ptr: *mut Mesh<Verlet3>, threads: usize) -> NacStatus {
+    status(|| {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        if threads > 1 {
+            mesh.set_solver(Solver::Parallel { threads })?;
+        } else {
+            mesh.set_solver(Solver::Serial)?;
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn snapshot_cloth_mesh(mesh_ptr: *mut Mesh<Verlet3>) -> *mut MeshSnapshot<Verlet3> {
+    guarded(std::ptr::null_mut(), || Ok(Box::into_raw(Box::new(non_null(mesh_ptr, "mesh")?.snapshot()?))))
+}
+
+// restores into the mesh the snapshot was taken from, node pointers stay valid
+#[no_mangle]
+pub unsafe extern fn restore_cloth_mesh(mesh_ptr: *mut Mesh<Verlet3>, snapshot_ptr: *const MeshSnapshot<Verlet3>) -> bool {
+    guarded(false, || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        mesh.restore(non_null(snapshot_ptr, "snapshot")?)?;
+        Ok(true)
+    })
+}
+
+// a fresh mesh with its own nodes, released with mesh_free
+#[no_mangle]
+pub unsafe extern fn mesh_from_snapshot(snapshot_ptr: *const MeshSnapshot<Verlet3>) -> *mut Mesh<Verlet3> {
+    guarded(std::ptr::null_mut(), || Ok(Box::into_raw(Box::new(Mesh::from_snapshot(non_null(snapshot_ptr, "snapshot")?)))))
+}
+
+#[no_mangle]
+pub unsafe extern fn snapshot_free(snapshot_ptr: *mut MeshSnapshot<Verlet3>) {
+    guarded((), || {
+        if !snapshot_ptr.is_null() {
+            drop(Box::from_raw(snapshot_ptr));
+        }
+        Ok(())
+    })
+}
+
+// a capacity of 0 stops recording and drops the history
+#[no_mangle]
+pub unsafe extern fn set_cloth_mesh_history(mesh_ptr: *mut Mesh<Verlet3>, capacity: usize) -> NacStatus {
+    status(|| {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        if capacity > 0 {
+            mesh.enable_history(capacity);
+        } else {
+            mesh.disable_history();
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn cloth_mesh_history_len(mesh_ptr: *mut Mesh<Verlet3>) -> usize {
+    guarded(0, || Ok(non_null(mesh_ptr, "mesh")?.history_len()))
+}
+
+// returns how many steps were actually undone
+#[no_mangle]
+pub unsafe extern fn rewind_cloth_mesh(mesh_ptr: *mut Mesh<Verlet3>, steps: usize) -> usize {
+    guarded(0, || non_null_mut(mesh_ptr, "mesh")?.rewind(steps))
+}
+
+#[no_mangle]
+pub unsafe extern fn step_back_cloth_mesh(mesh_ptr: *mut Mesh<Verlet3>) -> bool {
+    guarded(false, || non_null_mut(mesh_ptr, "mesh")?.step_back())
+}
+
+// takes ownership of other_ptr, which must not be used or freed afterwards
+#[no_mangle]
+pub unsafe extern fn merge_cloth_mesh(mesh_ptr: *mut Mesh<Verlet3>, other_ptr: *mut Mesh<Verlet3>) -> NacStatus {
+    status(|| {
+        if mesh_ptr == other_ptr {
+            return Err(NacError::InvalidArgument("a mesh cannot be merged into itself".to_owned()));
+        }
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        non_null(other_ptr, "other")?;
+        mesh.merge(*Box::from_raw(other_ptr));
+        Ok(())
+    })
+}
+
+unsafe fn node_handles<'a>(handles: *const NodeHandle, count: usize) -> Result<&'a [NodeHandle], NacError> {
+    if count == 0 {
+        Ok(&[])
+    } else {
+        Ok(std::slice::from_raw_parts(non_null(handles, "handles")?, count))
     }
 }
 
+// copies the nodes into a new mesh, free it like any other mesh
+#[no_mangle]
+pub unsafe extern fn extract_cloth_mesh(mesh_ptr: *mut Mesh<Verlet3>, handles: *const NodeHandle, count: usize) -> *mut Mesh<Verlet3> {
+    guarded(std::ptr::null_mut(), || {
+        let mesh = non_null(mesh_ptr, "mesh")?;
+        Ok(Box::into_raw(Box::new(mesh.extract(node_handles(handles, count)?)?)))
+    })
+}
+
+// moves the nodes out of mesh_ptr into a new mesh
+#[no_mangle]
+pub unsafe extern fn split_cloth_mesh(mesh_ptr: *mut Mesh<Verlet3>, handles: *const NodeHandle, count: usize) -> *mut Mesh<Verlet3> {
+    guarded(std::ptr::null_mut(), || {
+        let mesh = non_null_mut(mesh_ptr, "mesh")?;
+        Ok(Box::into_raw(Box::new(mesh.split(node_handles(handles, count)?))))
+    })
+}
+
+// The accessors return x and y as the two dimensional API always did, and drop z; the
+// ones ending in 3 return all three.
 #[repr(C)]
 pub struct CVecView {
     array: *mut CPoint2,
@@ -520,11 +1318,40 @@ pub struct CVecView {
 }
 
 #[repr(C)]
+pub struct CVecView3 {
+    array: *mut CPoint3,
+    size: usize,
+}
+
+#[repr(C)]
+#[derive(Default)]
 pub struct CPoint2 {
     x: f64,
     y: f64
 }
+
+impl From<Point3> for CPoint2 {
+    fn from(point: Point3) -> Self {
+        CPoint2 { x: point.x, y: point.y }
+    }
+}
+
 #[repr(C)]
+#[derive(Default)]
+pub struct CPoint3 {
+    x: f64,
+    y: f64,
+    z: f64
+}
+
+impl From<Point3> for CPoint3 {
+    fn from(point: Point3) -> Self {
+        CPoint3 { x: point.x, y: point.y, z: point.z }
+    }
+}
+
+#[repr(C)]
+#[derive(Default)]
 pub struct DualCPoint2 {
     x: f64,
     dx: f64,
@@ -532,135 +1359,308 @@ pub struct DualCPoint2 {
     dy: f64
 }
 
-// NOTE vector pointer not being reliably unpacked by python
+#[repr(C)]
+#[derive(Default)]
+pub struct DualCPoint3 {
+    x: f64,
+    dx: f64,
+    y: f64,
+    dy: f64,
+    z: f64,
+    dz: f64
+}
+
+unsafe fn mesh_positions<P: From<Point3>>(mesh_ptr: *mut Mesh<Verlet3>) -> Result<Box<[P]>, NacError> {
+    let mesh = non_null(mesh_ptr, "mesh")?;
+    Ok(mesh.nodes.iter()
+        .map(|node| Ok(P::from(read_node(node)?.data.position)))
+        .collect::<Result<Vec<P>, NacError>>()?
+        .into_boxed_slice())
+}
+
+// released with vector_free; an empty view with a null array on error
 #[no_mangle]
-pub unsafe extern fn get_cloth_mesh_positions(mesh_ptr: *mut Mesh<Verlet>)
+pub unsafe extern fn get_cloth_mesh_positions(mesh_ptr: *mut Mesh<Verlet3>)
     -> CVecView {
-    if !mesh_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        let positions: Vec<CPoint2> = (*mesh).nodes.iter().map( |node|
-            if let Ok(borrowed_node) = node.read() {
-                println!("{:?}, {:?}",borrowed_node.data.position.x,borrowed_node.data.position.y);
-                CPoint2 {
-                    x: borrowed_node.data.position.x.clone(),
-                    y: borrowed_node.data.position.y.clone()
-                }
-            } else { // FIXME(kevinc) return railed result instead of incorrect values
-                panic!("get_cloth_mesh_positions fail");
-                CPoint2 {
-                    x: 9999.0,
-                    y: 9999.0
-                }
-            }).collect();
+    let empty = CVecView { array: std::ptr::null_mut(), size: 0 };
+    guarded(empty, || {
+        let positions = mesh_positions::<CPoint2>(mesh_ptr)?;
         let positions_length: usize = positions.len();
-        CVecView {
-            array: Box::into_raw(Box::new(positions)) as *mut CPoint2,
+        Ok(CVecView {
+            array: Box::into_raw(positions) as *mut CPoint2,
             size: positions_length
-            }
-    } else {
-        panic!("get_cloth_mesh_positions fail");
-        let positions: Vec<CPoint2> = vec!();
-        let positions_length = 0;
-        CVecView {
-            array: Box::into_raw(Box::new(positions)) as *mut CPoint2,
+        })
+    })
+}
+
+// released with vector3_free
+#[no_mangle]
+pub unsafe extern fn get_cloth_mesh_positions3(mesh_ptr: *mut Mesh<Verlet3>)
+    -> CVecView3 {
+    let empty = CVecView3 { array: std::ptr::null_mut(), size: 0 };
+    guarded(empty, || {
+        let positions = mesh_positions::<CPoint3>(mesh_ptr)?;
+        let positions_length: usize = positions.len();
+        Ok(CVecView3 {
+            array: Box::into_raw(positions) as *mut CPoint3,
             size: positions_length
-        }
-    }
+        })
+    })
 }
 
 #[no_mangle]
-pub unsafe extern fn get_cloth_interconnector_count(mesh_ptr: *mut Mesh<Verlet>)
+pub unsafe extern fn get_cloth_interconnector_count(mesh_ptr: *mut Mesh<Verlet3>)
     -> usize {
-    //if !mesh_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        (*mesh).interconnectors.len()
-    //}
+    guarded(0, || Ok(non_null(mesh_ptr, "mesh")?.interconnectors.len()))
 }
 #[no_mangle]
-pub unsafe extern fn get_interconnector_constraint_count(connector_ptr: *mut Mesh<Verlet>, index: usize) -> usize {
-    if !connector_ptr.is_null() {
-        let connector = connector_ptr as *mut Mesh<Verlet>;
-        if let Some(constraints) = &(*connector).interconnectors[index].constraints {
-            constraints.len()
-        } else {
-            0
-        }
-    } else {
-        0
-    }
+pub unsafe extern fn get_interconnector_constraint_count(connector_ptr: *mut Mesh<Verlet3>, index: usize) -> usize {
+    guarded(0, || {
+        let mesh = non_null(connector_ptr, "mesh")?;
+        Ok(checked(&mesh.interconnectors, index)?.constraints.as_ref().map_or(0, Vec::len))
+    })
 }
+// the primary node and the constraint at `index`, in that order
+unsafe fn constraint_ends(mesh_ptr: *mut Mesh<Verlet3>, inter_index: usize, index: usize) -> Result<(Point3, Point3), NacError> {
+    let mesh = non_null(mesh_ptr, "mesh")?;
+    let connector = checked(&mesh.interconnectors, inter_index)?;
+    let constraint = checked(connector.constraints.as_ref().map_or(&[][..], Vec::as_slice), index)?;
+    Ok((read_node(&connector.node)?.data.position, read_node(constraint)?.data.position))
+}
+
 #[no_mangle]
-pub unsafe extern fn get_interconnector_constraint(connector_ptr: *mut Mesh<Verlet>, inter_index: usize, index: usize) -> DualCPoint2 {
-    if !connector_ptr.is_null() {
-        let connector = connector_ptr as *mut Mesh<Verlet>;
-        if let Some(constraints) = &(*connector).interconnectors[inter_index].constraints {
-            DualCPoint2 {
-                dx: constraints[index].read().unwrap().data.position.x,
-                dy: constraints[index].read().unwrap().data.position.y,
-                x: (*connector).interconnectors[inter_index].node.read().unwrap().data.position.x,
-                y: (*connector).interconnectors[inter_index].node.read().unwrap().data.position.y
-            }
-        } else {
-            panic!("None interconnector constraint");
-            DualCPoint2 {
-                dx: 0.0,
-                dy: 0.0,
-                x: (*connector).interconnectors[inter_index].node.read().unwrap().data.position.x,
-                y: (*connector).interconnectors[inter_index].node.read().unwrap().data.position.y
-            }
-        } 
-    } else {
-        panic!("get_interconnector constraint null pointer!!!");
-        DualCPoint2 {
-            dx: 0.0,
-            dy: 0.0,
-            x: 0.0,
-            y: 0.0
-        }
-    }
+pub unsafe extern fn get_interconnector_constraint(connector_ptr: *mut Mesh<Verlet3>, inter_index: usize, index: usize) -> DualCPoint2 {
+    guarded(DualCPoint2::default(), || {
+        let (node, constraint) = constraint_ends(connector_ptr, inter_index, index)?;
+        Ok(DualCPoint2 {
+            dx: constraint.x,
+            dy: constraint.y,
+            x: node.x,
+            y: node.y
+        })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn get_interconnector_constraint3(connector_ptr: *mut Mesh<Verlet3>, inter_index: usize, index: usize) -> DualCPoint3 {
+    guarded(DualCPoint3::default(), || {
+        let (node, constraint) = constraint_ends(connector_ptr, inter_index, index)?;
+        Ok(DualCPoint3 {
+            dx: constraint.x,
+            dy: constraint.y,
+            dz: constraint.z,
+            x: node.x,
+            y: node.y,
+            z: node.z
+        })
+    })
 }
 
 #[no_mangle]
 pub unsafe extern fn vector_free(vecview: CVecView) {
-    Box::from_raw(vecview.array);
+    guarded((), || {
+        if !vecview.array.is_null() {
+            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(vecview.array, vecview.size)));
+        }
+        Ok(())
+    })
 }
 
 #[no_mangle]
-pub unsafe extern fn mesh_free(ssv: *mut Mesh<Verlet>) {
-    if !ssv.is_null() {
-        let sv = ssv as *mut Mesh<Verlet>;
-        Box::from_raw(sv);
-    }
+pub unsafe extern fn vector3_free(vecview: CVecView3) {
+    guarded((), || {
+        if !vecview.array.is_null() {
+            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(vecview.array, vecview.size)));
+        }
+        Ok(())
+    })
 }
 
 #[no_mangle]
-pub unsafe extern fn mesh_node_count(ssv: *const Mesh<Verlet>)
+pub unsafe extern fn mesh_free(ssv: *mut Mesh<Verlet3>) {
+    guarded((), || {
+        if !ssv.is_null() {
+            drop(Box::from_raw(ssv));
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn mesh_node_count(ssv: *const Mesh<Verlet3>)
     -> u32
 {
-    let sv = ssv as *mut Mesh<Verlet>;
-    (*sv).nodes.len().try_into().unwrap()
+    guarded(0, || {
+        let mesh = non_null(ssv, "mesh")?;
+        u32::try_from(mesh.nodes.len()).map_err(|_| NacError::InvalidArgument("mesh has more nodes than a u32 counts".to_owned()))
+    })
+}
+
+unsafe fn node_position(mesh_ptr: *mut Mesh<Verlet3>, idx: usize) -> Result<Point3, NacError> {
+    let mesh = non_null(mesh_ptr, "mesh")?;
+    Ok(read_node(checked(&mesh.nodes, idx)?)?.data.position)
+}
+
+unsafe fn handle_position(mesh_ptr: *mut Mesh<Verlet3>, handle: NodeHandle) -> Result<Point3, NacError> {
+    let mesh = non_null(mesh_ptr, "mesh")?;
+    Ok(read_node(mesh.node(handle).ok_or(NacError::StaleHandle)?)?.data.position)
+}
+
+unsafe fn interpolated_position(mesh_ptr: *mut Mesh<Verlet3>, idx: usize, alpha: f64) -> Result<Point3, NacError> {
+    let mesh = non_null(mesh_ptr, "mesh")?;
+    let borrowed_node = read_node(checked(&mesh.nodes, idx)?)?;
+    let position = borrowed_node.data.position;
+    let previous = if borrowed_node.data.pinned { position } else { borrowed_node.data.delta_position() };
+    Ok(Point3 {
+        x: previous.x + (position.x - previous.x) * alpha,
+        y: previous.y + (position.y - previous.y) * alpha,
+        z: previous.z + (position.z - previous.z) * alpha
+    })
+}
+
+#[no_mangle]
+pub unsafe extern fn mesh_node_position(mesh_ptr: *mut Mesh<Verlet3>, idx: usize)
+    -> CPoint2 {
+    guarded(CPoint2::default(), || node_position(mesh_ptr, idx).map(CPoint2::from))
+}
+
+#[no_mangle]
+pub unsafe extern fn mesh_node_position3(mesh_ptr: *mut Mesh<Verlet3>, idx: usize)
+    -> CPoint3 {
+    guarded(CPoint3::default(), || node_position(mesh_ptr, idx).map(CPoint3::from))
+}
+
+#[no_mangle]
+pub unsafe extern fn mesh_node_position_by_handle(mesh_ptr: *mut Mesh<Verlet3>, handle: NodeHandle)
+    -> CPoint2 {
+    guarded(CPoint2::default(), || handle_position(mesh_ptr, handle).map(CPoint2::from))
+}
+
+#[no_mangle]
+pub unsafe extern fn mesh_node_position_by_handle3(mesh_ptr: *mut Mesh<Verlet3>, handle: NodeHandle)
+    -> CPoint3 {
+    guarded(CPoint3::default(), || handle_position(mesh_ptr, handle).map(CPoint3::from))
 }
 
+// position blended between the last two substeps, for drawing with advance_cloth_mesh's alpha
 #[no_mangle]
-pub unsafe extern fn mesh_node_position(mesh_ptr: *mut Mesh<Verlet>, idx: usize)
+pub unsafe extern fn mesh_node_interpolated_position(mesh_ptr: *mut Mesh<Verlet3>, idx: usize, alpha: f64)
     -> CPoint2 {
-    if !mesh_ptr.is_null() {
-        let mesh = mesh_ptr as *mut Mesh<Verlet>;
-        if let Ok(borrowed_node) = (*mesh).nodes[idx].read() {
-            CPoint2 {
-                x: borrowed_node.data.position.x,
-                y: borrowed_node.data.position.y
+    guarded(CPoint2::default(), || interpolated_position(mesh_ptr, idx, alpha).map(CPoint2::from))
+}
+
+#[no_mangle]
+pub unsafe extern fn mesh_node_interpolated_position3(mesh_ptr: *mut Mesh<Verlet3>, idx: usize, alpha: f64)
+    -> CPoint3 {
+    guarded(CPoint3::default(), || interpolated_position(mesh_ptr, idx, alpha).map(CPoint3::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verlet::{Verlet, Point2};
+    use crate::nac::Kinematic;
+
+    // a builder cloth with its columns turned about the pinned top row, so it swings once stepped
+    fn swinging(damping: Damping) -> Mesh<Verlet> {
+        let mesh: Mesh<Verlet> = Cloth::new_cloth(4, 3, 5, 20000, 0.0, 0.5, 0, damping);
+        for node in &mesh.nodes {
+            let mut node = node.write().unwrap();
+            if !node.data.is_pinned() {
+                let position = node.data.point();
+                let moved = Point::planar(position.x() + position.y() * 0.5f64.sin(), position.y() * 0.5f64.cos());
+                node.data = node.data.with_parts(moved, moved, false);
+            }
+        }
+        mesh
+    }
+
+    #[test]
+    fn alternating_timesteps_keep_the_velocity_continuous() {
+        let mut mesh = swinging(Damping::default());
+        let bottom = Arc::clone(mesh.nodes.last().unwrap());
+        // no more than gravity adds over the longer step; a per-step gravity or wind would
+        // halve and double the velocity as dt alternates
+        let most = 0.5 * builder_gravity(20000, 0.001) * 0.032;
+        let mut last = Point2::default();
+        for step in 0..40 {
+            let dt = if step % 2 == 0 { 0.016 } else { 0.032 };
+            mesh.update(dt, 4).unwrap();
+            let velocity = Kinematic::velocity(&bottom.read().unwrap().data, dt);
+            assert!(velocity.sub(last).length() < most);
+            last = velocity;
+        }
+    }
+
+    fn energy(mesh: &Mesh<Verlet>, dt: f64) -> f64 {
+        let acceleration = 0.5 * builder_gravity(20000, 0.001);
+        mesh.nodes.iter().map(|node| node.read().unwrap().data).filter(|data| !data.is_pinned()).map(|data| {
+            0.5 * Kinematic::velocity(&data, dt).length().powi(2) - acceleration * data.point().y()
+        }).sum::<f64>() + acceleration * 3.0 * (5.0 + 10.0 + 15.0)
+    }
+
+    #[test]
+    fn damping_takes_energy_out_of_a_swinging_cloth() {
+        let swing = |linear: f64| {
+            let mut mesh = swinging(Damping::new(linear, 0.0));
+            let start = energy(&mesh, 0.016);
+            for _ in 0..60 {
+                mesh.update(0.016, 20).unwrap();
             }
-        } else { // FIXME(kevinc) return railed result instead of incorrect values
-            CPoint2 {
-                x: 9999.0,
-                y: 9999.0
+            energy(&mesh, 0.016) / start
+        };
+        // the solver itself loses a little
+        assert!(swing(0.0) > 0.9);
+        assert!(swing(1.0) < 0.6);
+    }
+
+    #[test]
+    fn a_verlet3_cloth_falls_from_its_pinned_top_row() {
+        let mut mesh: Mesh<Verlet3> = Cloth::new_cloth(4, 3, 5, 20000, 0.0, 0.5, 0, Damping::default());
+        let start: Vec<Point3> = mesh.nodes.iter().map(|node| node.read().unwrap().data.point()).collect();
+        for _ in 0..30 {
+            mesh.update(0.016, 4).unwrap();
+        }
+        for (node, start) in mesh.nodes.iter().zip(start) {
+            let data = node.read().unwrap().data;
+            if start.y == 0.0 {
+                assert!(data.is_pinned());
+                assert_eq!(data.point(), start);
+            } else {
+                assert!(data.point().y > start.y);
+                assert_eq!(data.point().z, 0.0);
             }
         }
-    } else {
-        CPoint2 {
-            x: 9999.0,
-            y: 9999.0
+    }
+
+    fn positions<V: VerletNode>(mesh: &Mesh<V>) -> Vec<V::Point> {
+        mesh.nodes.iter().map(|node| node.read().unwrap().data.point()).collect()
+    }
+
+    fn stepped(mut mesh: Mesh<Verlet>, edit: impl FnOnce(&mut Mesh<Verlet>)) -> Vec<Point2> {
+        for _ in 0..5 {
+            mesh.update(0.016, 4).unwrap();
         }
+        edit(&mut mesh);
+        mesh.update(0.016, 4).unwrap();
+        positions(&mesh)
+    }
+
+    #[test]
+    fn cloth_edited_by_name_steps_with_its_new_parameters() {
+        let cloth = || Cloth::new_cloth(4, 3, 5, 20000, 3.0, 0.5, 7, Damping::default());
+        let unedited = stepped(cloth(), |_mesh| {});
+        let spring = stepped(cloth(), |mesh| assert_eq!(mesh.edit_interconnectors_by_name("cloth", |constraint: &mut Constraint| constraint.set_spring(0.1)), 17));
+        let spacing = stepped(cloth(), |mesh| assert_eq!(mesh.edit_interconnectors_by_name("cloth", |constraint: &mut Constraint| constraint.set_spacing(6.0)), 17));
+        assert_ne!(spring, unedited);
+        assert_ne!(spacing, unedited);
+        assert_ne!(spring, spacing);
+
+        // the same strength again: gusts carry on from where they were rather than starting over
+        let strength = 3.0 / REFERENCE_DT.powi(2);
+        let wind = stepped(cloth(), |mesh| assert_eq!(mesh.edit_selfconnectors_by_name("wind", |constraint: &mut Constraint| constraint.set_strength(strength, 0.0)), 9));
+        assert_eq!(wind, unedited);
+        let calm = stepped(cloth(), |mesh| { mesh.edit_selfconnectors_by_name("wind", |constraint: &mut Constraint| constraint.set_strength(0.0, 0.0)); });
+        assert_ne!(calm, unedited);
     }
 }
@@ -0,0 +1,166 @@
+use crate::nac::{Node, SharedNode, InterConnector, SelfConnector, SelfStepResolve, Mesh, read_node};
+use crate::error::NacError;
+use crate::handle::NodeHandle;
+use crate::snapshot::{Forks, fork_relation};
+
+use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, HashSet};
+
+fn node_key<T: Sync + Send + 'static>(node: &SharedNode<T>) -> usize {
+    Arc::as_ptr(node) as usize
+}
+
+// Connectors are kept when every node they touch is in the set, with their nodes looked
+// up in `nodes` (keyed by the original node) so the copy can point somewhere else, and
+// selfconnector relations passed through `relation`.
+fn connectors_within<T: Sync + Send + 'static>(
+    mesh: &Mesh<T>,
+    nodes: &HashMap<usize, SharedNode<T>>,
+    mut relation: impl FnMut(&Arc<dyn SelfStepResolve<T>>) -> Arc<dyn SelfStepResolve<T>>,
+) -> (Vec<InterConnector<T>>, Vec<SelfConnector<T>>) {
+    let within = |node: &SharedNode<T>| nodes.get(&node_key(node)).map(Arc::clone);
+
+    let interconnectors = mesh.interconnectors.iter().filter_map(|connector| {
+        let node = within(&connector.node)?;
+        let constraints = match &connector.constraints {
+            Some(constraints) => Some(constraints.iter().map(&within).collect::<Option<Vec<_>>>()?),
+            None => None,
+        };
+        let mut copy = InterConnector::with_relation(node, constraints, connector.relation().clone());
+        copy.name = connector.name.clone();
+        copy.tags = connector.tags.clone();
+        copy.enabled = connector.enabled;
+        Some(copy)
+    }).collect();
+
+    let selfconnectors = mesh.selfconnectors.iter().filter_map(|connector| {
+        let node = within(&connector.node)?;
+        Some(SelfConnector::stepped(&connector.name, node, relation(connector.relation())))
+    }).collect();
+
+    (interconnectors, selfconnectors)
+}
+
+impl<T: Sync + Send + 'static> Mesh<T> {
+    // a part split off or extracted keeps stepping the way its source did
+    fn with_settings(&self, mut mesh: Mesh<T>) -> Mesh<T> {
+        mesh.solver = self.solver;
+        mesh.jacobi = self.jacobi;
+        mesh.integration = self.integration.clone();
+        mesh.fixed_step = self.fixed_step;
+        mesh.poison_policy = self.poison_policy;
+        mesh.logger = self.logger.clone();
+        mesh.large_correction = self.large_correction;
+        mesh.on_break = self.on_break.clone();
+        mesh.damping = self.damping;
+        mesh.elapsed = self.elapsed;
+        mesh
+    }
+
+    // Takes over the nodes and connectors of `other`. Nodes the two meshes already share are
+    // kept once, which is how a rope built against a flag's corner node stays attached.
+    // Elapsed time, stepping and solver settings stay those of `self`.
+    pub fn merge(&mut self, other: Mesh<T>) {
+        self.sync_handles();
+        let known: HashSet<usize> = self.nodes.iter().map(node_key).collect();
+        let Mesh { nodes, interconnectors, selfconnectors, .. } = other;
+        nodes.into_iter()
+            .filter(|node| !known.contains(&node_key(node)))
+            .for_each(|node| { self.add_node(node); });
+        interconnectors.into_iter().for_each(|connector| { self.add_interconnector(connector); });
+        selfconnectors.into_iter().for_each(|connector| { self.add_selfconnector(connector); });
+    }
+
+    // Moves the given nodes into a mesh of their own, along with the connectors entirely
+    // among them. Connectors between the two parts are dropped. Stale handles are skipped.
+    pub fn split(&mut self, nodes: &[NodeHandle]) -> Mesh<T> {
+        self.sync_handles();
+        let moved: HashMap<usize, SharedNode<T>> = nodes.iter()
+            .filter_map(|&handle| self.node(handle))
+            .map(|node| (node_key(node), Arc::clone(node)))
+            .collect();
+        let (interconnectors, selfconnectors) = connectors_within(self, &moved, Arc::clone);
+        let order: Vec<SharedNode<T>> = self.nodes.iter()
+            .filter(|node| moved.contains_key(&node_key(node)))
+            .map(Arc::clone)
+            .collect();
+        self.remove_nodes(nodes);
+
+        self.with_settings(Mesh::new(order, interconnectors, selfconnectors))
+    }
+}
+
+impl<T: Sync + Send + Clone + 'static> Mesh<T> {
+    // A separate mesh holding copies of the given nodes and the connectors entirely among
+    // them, in mesh order; `self` is unchanged. Selfconnector relations with state of their
+    // own, like wind's generator, are forked as a snapshot does, so the two don't draw from
+    // one stream; other relations are shared.
+    pub fn extract(&self, nodes: &[NodeHandle]) -> Result<Mesh<T>, NacError> {
+        let copies: HashMap<usize, SharedNode<T>> = nodes.iter()
+            .filter_map(|&handle| self.node(handle))
+            .map(|node| Ok((node_key(node), Arc::new(RwLock::new(Node::clone(&*read_node(node)?))))))
+            .collect::<Result<_, NacError>>()?;
+        let mut forks = Forks::default();
+        let (interconnectors, selfconnectors) = connectors_within(self, &copies, |relation| fork_relation(relation, &mut forks));
+        let order: Vec<SharedNode<T>> = self.nodes.iter()
+            .filter_map(|node| copies.get(&node_key(node)).map(Arc::clone))
+            .collect();
+
+        Ok(self.with_settings(Mesh::new(order, interconnectors, selfconnectors)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nac::InterStepResolve;
+    use crate::constraint::Constraint;
+    use crate::verlet::Verlet;
+
+    fn shared(x: f64) -> SharedNode<Verlet> {
+        Arc::new(RwLock::new(Node::new(Verlet::new(x, 0.0))))
+    }
+
+    fn link(a: &SharedNode<Verlet>, b: &SharedNode<Verlet>, name: &str) -> InterConnector<Verlet> {
+        let distance: Arc<dyn InterStepResolve<Verlet>> = Arc::new(Constraint::Distance { spacing: 1.0, spring: 0.5, tear: None });
+        InterConnector::stepped(Arc::clone(a), Some(vec!(Arc::clone(b))), distance).with_name(name)
+    }
+
+    #[test]
+    fn merged_meshes_keep_shared_nodes_once() {
+        let corner = shared(0.0);
+        let (flag_end, rope_end) = (shared(-1.0), shared(1.0));
+        let mut flag = Mesh::new(vec!(Arc::clone(&flag_end), Arc::clone(&corner)), vec!(link(&flag_end, &corner, "flag")), vec!());
+        let rope = Mesh::new(vec!(Arc::clone(&corner), Arc::clone(&rope_end)), vec!(link(&corner, &rope_end, "rope")), vec!());
+
+        flag.merge(rope);
+        assert_eq!(flag.nodes.len(), 3);
+        assert_eq!(flag.nodes.iter().filter(|node| Arc::ptr_eq(node, &corner)).count(), 1);
+        assert_eq!(flag.interconnectors.len(), 2);
+        assert!(Arc::ptr_eq(&flag.interconnectors[1].node, &corner));
+    }
+
+    #[test]
+    fn extract_keeps_only_connectors_inside_the_set() {
+        let nodes: Vec<SharedNode<Verlet>> = (0..4).map(|i| shared(i as f64)).collect();
+        let interconnectors = vec!(link(&nodes[0], &nodes[1], "inside"), link(&nodes[1], &nodes[2], "across"), link(&nodes[2], &nodes[3], "outside"));
+        let wind: Arc<dyn SelfStepResolve<Verlet>> = Arc::new(Constraint::wind(0.5, 0.5, 7));
+        let selfconnectors = nodes.iter().map(|node| SelfConnector::stepped("wind", Arc::clone(node), Arc::clone(&wind))).collect();
+        let mesh = Mesh::new(nodes, interconnectors, selfconnectors);
+
+        let part = mesh.extract(&[mesh.node_handle(0).unwrap(), mesh.node_handle(1).unwrap()]).unwrap();
+        assert_eq!(part.nodes.len(), 2);
+        assert!(part.nodes.iter().all(|copy| mesh.nodes.iter().all(|node| !Arc::ptr_eq(copy, node))));
+        let names: Vec<&str> = part.interconnectors.iter().map(|connector| connector.name.as_str()).collect();
+        assert_eq!(names, vec!("inside"));
+        assert!(Arc::ptr_eq(&part.interconnectors[0].node, &part.nodes[0]));
+        assert_eq!(part.selfconnectors.len(), 2);
+        // the copy's wind shares a generator of its own
+        let rng = |connector: &SelfConnector<Verlet>| match connector.constraint::<Constraint>() {
+            Some(Constraint::Wind { rng, .. }) => Arc::clone(rng),
+            _ => panic!("not wind"),
+        };
+        assert!(!Arc::ptr_eq(&rng(&part.selfconnectors[0]), &rng(&mesh.selfconnectors[0])));
+        assert!(Arc::ptr_eq(&rng(&part.selfconnectors[0]), &rng(&part.selfconnectors[1])));
+    }
+}
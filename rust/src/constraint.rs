@@ -0,0 +1,251 @@
+use crate::nac::{Node, InterStepResolve, SelfStepResolve, GroupStepResolve, StepContext};
+use crate::snapshot::Forks;
+use crate::verlet::{VerletNode, Point, SharedRng, seeded_rng};
+use crate::verlet::{gravity_constraint, wind_constraint, accelerated_wind_constraint, internode_constraint, force_constraint, ground_boundary_constraint, ground_bound_gravity_constraint, damped};
+use crate::verlet::{angle_constraint, area_constraint, centroid_constraint, angle_error, area_error, centroid_error};
+
+use std::any::Any;
+use std::sync::{Arc, Mutex, PoisonError};
+
+// The built-in Verlet constraints as data, so a connector's parameters can be read back
+// with `InterConnector::constraint::<Constraint>()` and changed by swapping in an edited copy.
+// `Distance` relates two nodes, `Angle`, `Area` and `Centroid` relate a whole group (see
+// `InterConnector::group`) and the rest act on one; used on the wrong kind of connector they
+// leave the nodes as they are. A `delta` of None follows the step's dt.
+#[derive(Debug,Clone)]
+pub enum Constraint {
+    // with a `tear`, the connector is removed once a step leaves its nodes further apart
+    Distance { spacing: f64, spring: f64, tear: Option<Tear> },
+    // angle in radians at the primary node, between its first two constraints
+    Angle { angle: f64, stiffness: f64 },
+    Area { area: f64, stiffness: f64 },
+    Centroid { x: f64, y: f64, stiffness: f64 },
+    // `gravity` is twice the acceleration: nodes move by 0.5·gravity·delta² each step, and
+    // integrators apply an acceleration of 0.5·gravity
+    Gravity { gravity: f64, delta: Option<f64> },
+    BoundGravity { gravity: f64, boundary: f64, delta: Option<f64> },
+    GroundBoundary { boundary: f64 },
+    Force { horz_strength: f64, vert_strength: f64, delta: Option<f64> },
+    // Strengths are the furthest a gust moves a node in one step or, when `accelerated`,
+    // accelerations applied over the step's dt. `seed` is what `rng` was created from;
+    // wind connectors sharing a seed usually share the generator
+    Wind { horz_strength: f64, vert_strength: f64, accelerated: bool, seed: u64, rng: SharedRng },
+}
+
+// How far a distance constraint may stretch before it tears
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Tear {
+    // a multiple of the constraint's spacing, so it follows changes to the spacing
+    Ratio(f64),
+    // a fixed distance between the nodes
+    Distance(f64),
+}
+
+impl Tear {
+    pub fn limit(&self, spacing: f64) -> f64 {
+        match *self {
+            Tear::Ratio(ratio) => ratio * spacing,
+            Tear::Distance(distance) => distance,
+        }
+    }
+}
+
+impl Constraint {
+    pub fn wind(horz_strength: f64, vert_strength: f64, seed: u64) -> Constraint {
+        Constraint::Wind { horz_strength, vert_strength, accelerated: false, seed, rng: seeded_rng(seed) }
+    }
+
+    pub fn accelerated_wind(horz_strength: f64, vert_strength: f64, seed: u64) -> Constraint {
+        Constraint::Wind { horz_strength, vert_strength, accelerated: true, seed, rng: seeded_rng(seed) }
+    }
+
+    pub fn is_inter(&self) -> bool {
+        matches!(self, Constraint::Distance { .. })
+    }
+
+    pub fn is_group(&self) -> bool {
+        matches!(self, Constraint::Distance { .. } | Constraint::Angle { .. } | Constraint::Area { .. } | Constraint::Centroid { .. })
+    }
+
+    // The setters change the parameter on the variants that have it and
+    // report whether this one did, to pass straight to `Mesh::edit_*`.
+    pub fn set_spacing(&mut self, value: f64) -> bool {
+        match self {
+            Constraint::Distance { spacing, .. } => { *spacing = value; true },
+            _ => false,
+        }
+    }
+
+    pub fn set_spring(&mut self, value: f64) -> bool {
+        match self {
+            Constraint::Distance { spring, .. } => { *spring = value; true },
+            _ => false,
+        }
+    }
+
+    // None makes the constraint unbreakable again
+    pub fn set_tear(&mut self, value: Option<Tear>) -> bool {
+        match self {
+            Constraint::Distance { tear, .. } => { *tear = value; true },
+            _ => false,
+        }
+    }
+
+    pub fn set_stiffness(&mut self, value: f64) -> bool {
+        match self {
+            Constraint::Angle { stiffness, .. } | Constraint::Area { stiffness, .. } | Constraint::Centroid { stiffness, .. } => { *stiffness = value; true },
+            _ => false,
+        }
+    }
+
+    pub fn set_gravity(&mut self, value: f64) -> bool {
+        match self {
+            Constraint::Gravity { gravity, .. } | Constraint::BoundGravity { gravity, .. } => { *gravity = value; true },
+            _ => false,
+        }
+    }
+
+    pub fn set_boundary(&mut self, value: f64) -> bool {
+        match self {
+            Constraint::BoundGravity { boundary, .. } | Constraint::GroundBoundary { boundary } => { *boundary = value; true },
+            _ => false,
+        }
+    }
+
+    // wind keeps drawing from the same generator, so gusts carry on where they were
+    pub fn set_strength(&mut self, horz: f64, vert: f64) -> bool {
+        match self {
+            Constraint::Force { horz_strength, vert_strength, .. } | Constraint::Wind { horz_strength, vert_strength, .. } => {
+                *horz_strength = horz;
+                *vert_strength = vert;
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+fn node_distance<V: VerletNode>(node1: &Node<V>, node2: &Node<V>) -> f64 {
+    node1.data.point().sub(node2.data.point()).length()
+}
+
+impl<V: VerletNode> InterStepResolve<V> for Constraint {
+    fn resolve(&self, primary_node: &Node<V>, secondary_node: &Node<V>, _context: &StepContext) -> (Node<V>, Node<V>) {
+        match *self {
+            Constraint::Distance { spacing, spring, .. } => internode_constraint(primary_node, secondary_node, spacing, spring),
+            _ => (*primary_node, *secondary_node),
+        }
+    }
+
+    fn breaks(&self, primary_node: &Node<V>, secondary_node: &Node<V>) -> bool {
+        match *self {
+            Constraint::Distance { spacing, tear: Some(tear), .. } => node_distance(primary_node, secondary_node) > tear.limit(spacing),
+            _ => false,
+        }
+    }
+
+    fn error(&self, primary_node: &Node<V>, secondary_node: &Node<V>) -> Option<f64> {
+        match *self {
+            Constraint::Distance { spacing, .. } => Some((node_distance(primary_node, secondary_node) - spacing).abs()),
+            _ => Some(0.0),
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+// `Distance` over a group pulls each constraint towards the primary node in turn, as it would pairwise
+impl<V: VerletNode> GroupStepResolve<V> for Constraint {
+    fn resolve(&self, nodes: &[&Node<V>], _context: &StepContext) -> Vec<Node<V>> {
+        let mut constrained: Vec<Node<V>> = nodes.iter().map(|&&node| node).collect();
+        match *self {
+            Constraint::Distance { spacing, spring, .. } => (1..constrained.len()).for_each(|idx| {
+                let (node, constraint) = internode_constraint(&constrained[0], &constrained[idx], spacing, spring);
+                constrained[0] = node;
+                constrained[idx] = constraint;
+            }),
+            Constraint::Angle { angle, stiffness } => constrained = angle_constraint(nodes, angle, stiffness),
+            Constraint::Area { area, stiffness } => constrained = area_constraint(nodes, area, stiffness),
+            Constraint::Centroid { x, y, stiffness } => constrained = centroid_constraint(nodes, x, y, stiffness),
+            _ => (),
+        }
+        constrained
+    }
+
+    // any of the constraints pulled too far from the primary node tears the whole group
+    fn breaks(&self, nodes: &[&Node<V>]) -> bool {
+        match (self, nodes.split_first()) {
+            (Constraint::Distance { spacing, tear: Some(tear), .. }, Some((primary, constraints))) =>
+                constraints.iter().any(|constraint| node_distance(primary, constraint) > tear.limit(*spacing)),
+            _ => false,
+        }
+    }
+
+    // the worst of the distances from the primary node, the angle in radians, the area as
+    // the difference between the sides of squares of the two areas, or the centroid's distance
+    fn error(&self, nodes: &[&Node<V>]) -> Option<f64> {
+        match (self, nodes.split_first()) {
+            (Constraint::Distance { spacing, .. }, Some((primary, constraints))) => Some(constraints.iter()
+                .map(|constraint| (node_distance(primary, constraint) - spacing).abs())
+                .fold(0.0, f64::max)),
+            (Constraint::Angle { angle, .. }, _) => Some(angle_error(nodes, *angle)),
+            (Constraint::Area { area, .. }, _) => Some(area_error(nodes, *area)),
+            (Constraint::Centroid { x, y, .. }, _) => Some(centroid_error(nodes, *x, *y)),
+            _ => Some(0.0),
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+impl<V: VerletNode> SelfStepResolve<V> for Constraint {
+    fn resolve(&self, node: &Node<V>, context: &StepContext) -> Node<V> {
+        match self {
+            Constraint::Distance { .. } | Constraint::Angle { .. } | Constraint::Area { .. } | Constraint::Centroid { .. } => *node,
+            // damping goes where the velocity is carried on, unless the integrator is doing that
+            Constraint::Gravity { gravity, delta } => {
+                let delta = delta.unwrap_or(context.dt);
+                gravity_constraint(&damped(node, delta, &context.damping), delta, *gravity)
+            },
+            // the integrator applies the fall above the boundary, what is left is the ground
+            Constraint::BoundGravity { boundary, .. } if context.integrated => ground_boundary_constraint(node, *boundary),
+            Constraint::BoundGravity { gravity, boundary, delta } => {
+                let delta = delta.unwrap_or(context.dt);
+                ground_bound_gravity_constraint(&damped(node, delta, &context.damping), delta, *gravity, *boundary)
+            },
+            Constraint::GroundBoundary { boundary } => ground_boundary_constraint(node, *boundary),
+            Constraint::Force { horz_strength, vert_strength, delta } => force_constraint(node, delta.unwrap_or(context.dt), *horz_strength, *vert_strength),
+            // the generator only holds a place in its sequence, a panic elsewhere cannot leave it half written
+            Constraint::Wind { horz_strength, vert_strength, accelerated, rng, .. } => {
+                let rng = &mut *rng.lock().unwrap_or_else(PoisonError::into_inner);
+                if *accelerated {
+                    accelerated_wind_constraint(node, context.dt, *horz_strength, *vert_strength, rng)
+                } else {
+                    wind_constraint(node, *horz_strength, *vert_strength, rng)
+                }
+            },
+        }
+    }
+
+    // wind connectors that shared a generator share its copy
+    fn fork(&self, forks: &mut Forks) -> Option<Arc<dyn SelfStepResolve<V>>> {
+        match self {
+            Constraint::Wind { horz_strength, vert_strength, accelerated, seed, rng } => Some(Arc::new(Constraint::Wind {
+                horz_strength: *horz_strength,
+                vert_strength: *vert_strength,
+                accelerated: *accelerated,
+                seed: *seed,
+                rng: forks.fork(rng, |rng| Mutex::new(rng.lock().unwrap_or_else(PoisonError::into_inner).clone())),
+            })),
+            _ => None,
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
@@ -0,0 +1,32 @@
+use crate::nac::Mesh;
+
+// Energy a mesh loses as its nodes move, applied where their velocity is integrated: by
+// gravity constraints carrying it into the next step, or by the mesh's integrator.
+// Both are 0 by default, which leaves motion as it was.
+#[derive(Debug,Default,Clone,Copy,PartialEq)]
+pub struct Damping {
+    // rate velocity decays at, per second, on top of each node's own damping
+    pub linear: f64,
+    // quadratic air drag, slowing a node by drag·speed² over its mass
+    pub drag: f64,
+}
+
+impl Damping {
+    pub fn new(linear: f64, drag: f64) -> Self {
+        Damping { linear, drag }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.linear == 0.0 && self.drag == 0.0
+    }
+}
+
+impl<T: Sync + Send + 'static> Mesh<T> {
+    pub fn set_damping(&mut self, damping: Damping) {
+        self.damping = damping;
+    }
+
+    pub fn damping(&self) -> Damping {
+        self.damping
+    }
+}
@@ -0,0 +1,217 @@
+use crate::scene::SceneError;
+use crate::poison::Poisoning;
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::fmt;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+
+// Everything the Rust APIs report instead of panicking. The C API turns these into a
+// `NacStatus` and keeps the message for `nac_last_error()`.
+#[derive(Debug)]
+pub enum NacError {
+    // a pointer argument of the C API was null, named by the argument
+    NullPointer(&'static str),
+    IndexOutOfRange { index: usize, len: usize },
+    // a handle whose slot was removed, or one that never belonged to the mesh
+    StaleHandle,
+    // a thread panicked while it held a node's lock, so the node may be half updated;
+    // the step fills in which node and connectors, a bare read or write leaves it empty
+    PoisonedNode(Poisoning),
+    InvalidArgument(String),
+    Scene(SceneError),
+    // a panic caught at the C boundary, with its message when it had one
+    Panic(String),
+}
+
+impl NacError {
+    pub fn status(&self) -> NacStatus {
+        match self {
+            NacError::NullPointer(_) => NacStatus::NullPointer,
+            NacError::IndexOutOfRange { .. } => NacStatus::IndexOutOfRange,
+            NacError::StaleHandle => NacStatus::StaleHandle,
+            NacError::PoisonedNode(_) => NacStatus::PoisonedNode,
+            NacError::InvalidArgument(_) => NacStatus::InvalidArgument,
+            NacError::Scene(_) => NacStatus::Scene,
+            NacError::Panic(_) => NacStatus::Panic,
+        }
+    }
+}
+
+impl fmt::Display for NacError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NacError::NullPointer(name) => write!(f, "{} is null", name),
+            NacError::IndexOutOfRange { index, len } => write!(f, "index {} is out of range for length {}", index, len),
+            NacError::StaleHandle => write!(f, "handle does not refer to anything in the mesh"),
+            NacError::PoisonedNode(poisoning) => write!(f, "lock of {} poisoned by a panic during an earlier update", poisoning),
+            NacError::InvalidArgument(reason) => write!(f, "invalid argument: {}", reason),
+            NacError::Scene(err) => write!(f, "{}", err),
+            NacError::Panic(message) => write!(f, "panicked: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for NacError {}
+
+impl From<SceneError> for NacError {
+    fn from(err: SceneError) -> Self {
+        NacError::Scene(err)
+    }
+}
+
+// bounds-checked indexing for the index based accessors
+pub(crate) fn checked<T>(items: &[T], index: usize) -> Result<&T, NacError> {
+    items.get(index).ok_or(NacError::IndexOutOfRange { index, len: items.len() })
+}
+
+// Outcome of the last C API call on the calling thread. Functions without a result of their
+// own return it directly; the others return a null, zero or default value on failure and
+// leave the status to `nac_last_status()`.
+#[repr(C)]
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum NacStatus {
+    Ok = 0,
+    NullPointer = 1,
+    IndexOutOfRange = 2,
+    StaleHandle = 3,
+    PoisonedNode = 4,
+    InvalidArgument = 5,
+    Scene = 6,
+    Panic = 7,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<(NacStatus, Option<CString>)> = const { RefCell::new((NacStatus::Ok, None)) };
+}
+
+fn set_last_error(error: &NacError) {
+    // interior nul bytes cannot cross into C, drop them rather than the message
+    let message = CString::new(error.to_string().replace('\0', "")).ok();
+    LAST_ERROR.with(|last| *last.borrow_mut() = (error.status(), message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = (NacStatus::Ok, None));
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map_or_else(|| "unknown panic".to_owned(), |message| (*message).to_owned()),
+    }
+}
+
+// Runs the body of a C API function. Errors, and panics from relations or anywhere else,
+// become the thread's last error and `fallback` is returned in their place, so nothing
+// unwinds into the host.
+pub(crate) fn guarded<R, F>(fallback: R, body: F) -> R
+    where F: FnOnce() -> Result<R, NacError>
+{
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(value)) => {
+            clear_last_error();
+            value
+        },
+        Ok(Err(error)) => {
+            set_last_error(&error);
+            fallback
+        },
+        Err(payload) => {
+            set_last_error(&NacError::Panic(panic_message(payload)));
+            fallback
+        },
+    }
+}
+
+// `guarded` for the functions whose only result is the status
+pub(crate) fn status<F>(body: F) -> NacStatus
+    where F: FnOnce() -> Result<(), NacError>
+{
+    guarded((), body);
+    nac_last_status()
+}
+
+// C pointer arguments as references, null being an error
+pub(crate) unsafe fn non_null<'a, T>(ptr: *const T, name: &'static str) -> Result<&'a T, NacError> {
+    ptr.as_ref().ok_or(NacError::NullPointer(name))
+}
+
+pub(crate) unsafe fn non_null_mut<'a, T>(ptr: *mut T, name: &'static str) -> Result<&'a mut T, NacError> {
+    ptr.as_mut().ok_or(NacError::NullPointer(name))
+}
+
+#[no_mangle]
+pub extern fn nac_last_status() -> NacStatus {
+    LAST_ERROR.with(|last| last.borrow().0)
+}
+
+// Message for the last failed C API call on this thread, or null if it succeeded. The string
+// stays owned by nac and is valid until the next call on the same thread; do not free it.
+#[no_mangle]
+pub extern fn nac_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().1.as_ref().map_or(std::ptr::null(), |message| message.as_ptr()))
+}
+
+#[no_mangle]
+pub extern fn nac_clear_error() {
+    clear_last_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nac::{Node, Mesh, SelfConnector, StepContext};
+    use crate::cloth::{mesh_node_count, mesh_node_position3, update_cloth_mesh};
+    use crate::verlet::Verlet3;
+
+    use std::ffi::CStr;
+    use std::sync::{Arc, RwLock};
+
+    fn last_error() -> Option<String> {
+        let message = nac_last_error();
+        if message.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_owned())
+        }
+    }
+
+    fn one_node(relation: impl Fn(&Node<Verlet3>, &StepContext) -> Node<Verlet3> + Send + Sync + 'static) -> *mut Mesh<Verlet3> {
+        let node = Arc::new(RwLock::new(Node::new(Verlet3::new(0.0, 0.0, 0.0))));
+        let selfconnector = SelfConnector::stepped("relation", Arc::clone(&node), Arc::new(relation));
+        Box::into_raw(Box::new(Mesh::new(vec!(node), vec!(), vec!(selfconnector))))
+    }
+
+    #[test]
+    fn a_null_mesh_is_reported_by_name() {
+        assert_eq!(unsafe { mesh_node_count(std::ptr::null()) }, 0);
+        assert_eq!(nac_last_status(), NacStatus::NullPointer);
+        assert_eq!(last_error().as_deref(), Some("mesh is null"));
+    }
+
+    #[test]
+    fn an_index_past_the_nodes_is_out_of_range() {
+        let mesh = one_node(|node, _context| *node);
+        unsafe { mesh_node_position3(mesh, 5) };
+        assert_eq!(nac_last_status(), NacStatus::IndexOutOfRange);
+        assert_eq!(last_error().as_deref(), Some("index 5 is out of range for length 1"));
+
+        // the next call that succeeds clears it
+        assert_eq!(unsafe { mesh_node_count(mesh) }, 1);
+        assert_eq!(nac_last_status(), NacStatus::Ok);
+        assert_eq!(last_error(), None);
+        drop(unsafe { Box::from_raw(mesh) });
+    }
+
+    #[test]
+    fn a_panicking_relation_does_not_unwind_into_the_host() {
+        let mesh = one_node(|_node, _context| panic!("relation failed"));
+        assert_eq!(unsafe { update_cloth_mesh(mesh, 0.016, 1) }, NacStatus::Panic);
+        assert_eq!(nac_last_status(), NacStatus::Panic);
+        assert_eq!(last_error().as_deref(), Some("panicked: relation failed"));
+        drop(unsafe { Box::from_raw(mesh) });
+    }
+}
@@ -0,0 +1,215 @@
+// Generational handles for mesh entries. A handle keeps naming the same node or
+// connector while others are added and removed around it, and stops resolving
+// once its entry is removed, even if the slot is later reused.
+
+#[derive(Debug,Default,Clone,Copy,PartialEq,Eq,Hash)]
+#[repr(C)]
+pub struct NodeHandle {
+    pub slot: u32,
+    pub generation: u32,
+}
+
+#[derive(Debug,Default,Clone,Copy,PartialEq,Eq,Hash)]
+#[repr(C)]
+pub struct InterHandle {
+    pub slot: u32,
+    pub generation: u32,
+}
+
+#[derive(Debug,Default,Clone,Copy,PartialEq,Eq,Hash)]
+#[repr(C)]
+pub struct SelfHandle {
+    pub slot: u32,
+    pub generation: u32,
+}
+
+pub trait Handle: Copy + PartialEq {
+    fn from_parts(slot: u32, generation: u32) -> Self;
+    fn slot(&self) -> u32;
+    fn generation(&self) -> u32;
+
+    // generation 0 is never handed out, so the zeroed handle is always invalid
+    fn is_null(&self) -> bool {
+        self.generation() == 0
+    }
+}
+
+impl Handle for NodeHandle {
+    fn from_parts(slot: u32, generation: u32) -> Self { Self { slot, generation } }
+    fn slot(&self) -> u32 { self.slot }
+    fn generation(&self) -> u32 { self.generation }
+}
+
+impl Handle for InterHandle {
+    fn from_parts(slot: u32, generation: u32) -> Self { Self { slot, generation } }
+    fn slot(&self) -> u32 { self.slot }
+    fn generation(&self) -> u32 { self.generation }
+}
+
+impl Handle for SelfHandle {
+    fn from_parts(slot: u32, generation: u32) -> Self { Self { slot, generation } }
+    fn slot(&self) -> u32 { self.slot }
+    fn generation(&self) -> u32 { self.generation }
+}
+
+#[derive(Clone)]
+struct Slot {
+    generation: u32,
+    index: Option<usize>,
+}
+
+// Maps handles to positions in a Vec kept alongside it. `handles` mirrors the Vec
+// order; entries are swap_removed from both, so a removal only moves the last entry.
+#[derive(Clone)]
+pub struct Slots<H: Handle> {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    handles: Vec<H>,
+}
+
+impl<H: Handle> Slots<H> {
+    pub fn new() -> Self {
+        Self {
+            slots: vec!(),
+            free: vec!(),
+            handles: vec!(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    // hand out a handle for an entry appended to the end of the Vec
+    pub fn push(&mut self) -> H {
+        let index = self.handles.len();
+        let handle = match self.free.pop() {
+            Some(slot) => {
+                let entry = &mut self.slots[slot as usize];
+                entry.index = Some(index);
+                H::from_parts(slot, entry.generation)
+            },
+            None => {
+                self.slots.push(Slot { generation: 1, index: Some(index) });
+                H::from_parts((self.slots.len() - 1) as u32, 1)
+            }
+        };
+        self.handles.push(handle);
+        handle
+    }
+
+    // give handles to entries pushed straight onto the Vec
+    pub fn sync(&mut self, len: usize) {
+        while self.handles.len() < len {
+            self.push();
+        }
+    }
+
+    pub fn handle(&self, index: usize) -> Option<H> {
+        self.handles.get(index).copied()
+    }
+
+    pub fn index(&self, handle: H) -> Option<usize> {
+        match self.slots.get(handle.slot() as usize) {
+            Some(entry) if entry.generation == handle.generation() => entry.index,
+            _ => None,
+        }
+    }
+
+    // forget the entry at `index` after it was swap_removed from the Vec,
+    // which moved the last entry into its place
+    pub fn remove_at(&mut self, index: usize) -> H {
+        let handle = self.handles.swap_remove(index);
+        let entry = &mut self.slots[handle.slot() as usize];
+        entry.index = None;
+        entry.generation = entry.generation.wrapping_add(1).max(1);
+        self.free.push(handle.slot());
+        if let Some(moved) = self.handles.get(index) {
+            self.slots[moved.slot() as usize].index = Some(index);
+        }
+        handle
+    }
+}
+
+impl<H: Handle> Default for Slots<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nac::{Node, SharedNode, InterConnector, StepContext, Mesh};
+    use crate::verlet::Verlet;
+
+    use std::sync::{Arc, RwLock};
+
+    fn node(x: f64) -> SharedNode<Verlet> {
+        Arc::new(RwLock::new(Node::new(Verlet::new(x, 0.0))))
+    }
+
+    fn link(a: &SharedNode<Verlet>, b: &SharedNode<Verlet>) -> InterConnector<Verlet> {
+        InterConnector::stepped(Arc::clone(a), Some(vec!(Arc::clone(b))),
+            Arc::new(|a: &Node<Verlet>, b: &Node<Verlet>, _context: &StepContext| (*a, *b)))
+    }
+
+    fn x(mesh: &Mesh<Verlet>, handle: NodeHandle) -> f64 {
+        mesh.node(handle).unwrap().read().unwrap().data.position.x
+    }
+
+    #[test]
+    fn handles_follow_their_entries_across_removals() {
+        let mut mesh: Mesh<Verlet> = Mesh::new(vec!(), vec!(), vec!());
+        let handles: Vec<NodeHandle> = (0..4).map(|i| mesh.add_node(node(i as f64))).collect();
+        mesh.remove_node(handles[1]).unwrap();
+
+        assert!(mesh.node(handles[1]).is_none());
+        assert_eq!(x(&mesh, handles[0]), 0.0);
+        assert_eq!(x(&mesh, handles[2]), 2.0);
+        assert_eq!(x(&mesh, handles[3]), 3.0);
+
+        // the freed slot is reused under a new generation, the old handle stays stale
+        let reused = mesh.add_node(node(4.0));
+        assert_eq!(reused.slot, handles[1].slot);
+        assert_ne!(reused.generation, handles[1].generation);
+        assert!(mesh.node(handles[1]).is_none());
+        assert_eq!(x(&mesh, reused), 4.0);
+        assert!(mesh.remove_node(handles[1]).is_none());
+    }
+
+    #[test]
+    fn removing_a_node_removes_its_connectors() {
+        let nodes: Vec<SharedNode<Verlet>> = (0..3).map(|i| node(i as f64)).collect();
+        let interconnectors = vec!(link(&nodes[0], &nodes[1]), link(&nodes[1], &nodes[2]), link(&nodes[2], &nodes[0]));
+        let mut mesh = Mesh::new(nodes, interconnectors, vec!());
+        let kept = mesh.interconnector_handle(1).unwrap();
+        let first = mesh.node_handle(0).unwrap();
+
+        mesh.remove_node(first).unwrap();
+        assert_eq!(mesh.nodes.len(), 2);
+        assert_eq!(mesh.interconnectors.len(), 1);
+        let connector = mesh.interconnector(kept).unwrap();
+        assert_eq!(connector.node.read().unwrap().data.position.x, 1.0);
+    }
+
+    #[test]
+    fn split_moves_nodes_and_connectors_among_them() {
+        let nodes: Vec<SharedNode<Verlet>> = (0..4).map(|i| node(i as f64)).collect();
+        let interconnectors = vec!(link(&nodes[0], &nodes[1]), link(&nodes[1], &nodes[2]), link(&nodes[2], &nodes[3]));
+        let mut mesh = Mesh::new(nodes, interconnectors, vec!());
+        let moved = [mesh.node_handle(2).unwrap(), mesh.node_handle(3).unwrap()];
+        let kept = mesh.node_handle(0).unwrap();
+
+        let part = mesh.split(&moved);
+        assert_eq!((mesh.nodes.len(), mesh.interconnectors.len()), (2, 1));
+        assert_eq!((part.nodes.len(), part.interconnectors.len()), (2, 1));
+        assert!(moved.iter().all(|&handle| mesh.node(handle).is_none()));
+        assert_eq!(x(&mesh, kept), 0.0);
+        assert_eq!(part.nodes[0].read().unwrap().data.position.x, 2.0);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::nac::{Node, SharedNode, Mesh, read_node, write_node};
+use crate::error::NacError;
+
+use std::sync::Arc;
+use std::collections::VecDeque;
+
+// Node state from before each of the last `capacity` steps, oldest first. Frames keep the
+// nodes they were read from, so rewinding writes back into the same Arcs and still lines up
+// after nodes are added or removed. Frames cover the same nodes as a snapshot, including
+// those only reachable through connectors, but leave out quarantined ones. Only node data
+// and elapsed time are recorded; connectors and relation-held state (such as wind
+// generators) stay as they are.
+pub struct History<T: Sync + Send + 'static> {
+    capacity: usize,
+    frames: VecDeque<Frame<T>>,
+    capture: fn(&SharedNode<T>) -> Result<Node<T>, NacError>,
+}
+
+struct Frame<T: Sync + Send + 'static> {
+    nodes: Vec<(SharedNode<T>, Node<T>)>,
+    elapsed: f64,
+}
+
+impl<T: Sync + Send + Clone + 'static> History<T> {
+    pub fn new(capacity: usize) -> Self {
+        History {
+            capacity,
+            frames: VecDeque::with_capacity(capacity),
+            capture: |node| Ok(read_node(node)?.clone()),
+        }
+    }
+}
+
+impl<T: Sync + Send + 'static> History<T> {
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    pub(crate) fn record(&mut self, nodes: &[SharedNode<T>], elapsed: f64) -> Result<(), NacError> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+        let capture = self.capture;
+        let nodes = nodes.iter().map(|node| Ok((Arc::clone(node), capture(node)?))).collect::<Result<_, NacError>>()?;
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(Frame { nodes, elapsed });
+        Ok(())
+    }
+
+    // drops the newest `steps` frames and returns the oldest of them
+    fn pop(&mut self, steps: usize) -> Option<Frame<T>> {
+        (0..steps).filter_map(|_| self.frames.pop_back()).last()
+    }
+}
+
+impl<T: Sync + Send + 'static> Mesh<T> {
+    pub fn history(&self) -> Option<&History<T>> {
+        self.history.as_ref()
+    }
+
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, History::len)
+    }
+
+    // Puts nodes back as they were `steps` updates ago, or as far back as the history
+    // goes, and forgets the rewound steps. Returns how many steps were undone.
+    pub fn rewind(&mut self, steps: usize) -> Result<usize, NacError> {
+        let history = match &mut self.history {
+            Some(history) => history,
+            None => return Ok(0),
+        };
+        let steps = steps.min(history.len());
+        if let Some(frame) = history.pop(steps) {
+            for (shared, node) in frame.nodes {
+                *write_node(&shared)? = node;
+            }
+            self.elapsed = frame.elapsed;
+        }
+        Ok(steps)
+    }
+
+    pub fn step_back(&mut self) -> Result<bool, NacError> {
+        Ok(self.rewind(1)? == 1)
+    }
+}
+
+impl<T: Sync + Send + Clone + 'static> Mesh<T> {
+    // record the node state before each of the next steps, keeping the last `capacity`
+    pub fn enable_history(&mut self, capacity: usize) {
+        self.history = Some(History::new(capacity));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nac::{SelfConnector, SelfStepResolve};
+    use crate::constraint::Constraint;
+    use crate::verlet::Verlet;
+
+    use std::sync::RwLock;
+
+    #[test]
+    fn rewind_restores_nodes_only_reachable_through_connectors() {
+        let listed = Arc::new(RwLock::new(Node::new(Verlet::new(0.0, 0.0))));
+        let unlisted = Arc::new(RwLock::new(Node::new(Verlet::new(1.0, 0.0))));
+        let gravity: Arc<dyn SelfStepResolve<Verlet>> = Arc::new(Constraint::Gravity { gravity: 10.0, delta: None });
+        let selfconnectors = vec!(
+            SelfConnector::stepped("gravity", Arc::clone(&listed), Arc::clone(&gravity)),
+            SelfConnector::stepped("gravity", Arc::clone(&unlisted), gravity));
+        let mut mesh = Mesh::new(vec!(Arc::clone(&listed)), vec!(), selfconnectors);
+        mesh.enable_history(4);
+
+        mesh.update(0.016, 1).unwrap();
+        mesh.update(0.016, 1).unwrap();
+        assert_ne!(unlisted.read().unwrap().data.position.y, 0.0);
+
+        assert_eq!(mesh.rewind(2).unwrap(), 2);
+        assert_eq!(listed.read().unwrap().data.position.y, 0.0);
+        assert_eq!(unlisted.read().unwrap().data.position.y, 0.0);
+        assert_eq!(unlisted.read().unwrap().data.position.x, 1.0);
+    }
+}
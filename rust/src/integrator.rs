@@ -0,0 +1,156 @@
+use crate::nac::{Kinematic, Integrator, Mesh, SharedNode, StepContext, SelfStepResolve};
+use crate::error::NacError;
+
+use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+
+// Position Verlet: carries the velocity of the last step and adds a·dt².
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+pub struct VerletIntegrator;
+
+// Velocity first, then position with the new velocity.
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+pub struct SemiImplicitEuler;
+
+// Classic fourth order Runge-Kutta over position and velocity.
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+pub struct Rk4;
+
+fn advance<T: Kinematic>(position: T::Vector, rate: T::Vector, dt: f64) -> T::Vector {
+    T::add(position, T::scale(rate, dt))
+}
+
+impl<T: Kinematic> Integrator<T> for VerletIntegrator {
+    fn integrate(&self, node: &T, acceleration: &dyn Fn(T::Vector, T::Vector) -> T::Vector, dt: f64) -> T {
+        let (position, velocity) = (node.position(), node.velocity(dt));
+        let travelled = advance::<T>(T::scale(velocity, dt), acceleration(position, velocity), dt * dt);
+        node.moved(T::add(position, travelled), T::scale(travelled, 1.0 / dt), dt)
+    }
+}
+
+impl<T: Kinematic> Integrator<T> for SemiImplicitEuler {
+    fn integrate(&self, node: &T, acceleration: &dyn Fn(T::Vector, T::Vector) -> T::Vector, dt: f64) -> T {
+        let (position, velocity) = (node.position(), node.velocity(dt));
+        let velocity = advance::<T>(velocity, acceleration(position, velocity), dt);
+        node.moved(advance::<T>(position, velocity, dt), velocity, dt)
+    }
+}
+
+impl<T: Kinematic> Integrator<T> for Rk4 {
+    fn integrate(&self, node: &T, acceleration: &dyn Fn(T::Vector, T::Vector) -> T::Vector, dt: f64) -> T {
+        let (position, velocity) = (node.position(), node.velocity(dt));
+        let k1 = (velocity, acceleration(position, velocity));
+        let (p2, v2) = (advance::<T>(position, k1.0, dt / 2.0), advance::<T>(velocity, k1.1, dt / 2.0));
+        let k2 = (v2, acceleration(p2, v2));
+        let (p3, v3) = (advance::<T>(position, k2.0, dt / 2.0), advance::<T>(velocity, k2.1, dt / 2.0));
+        let k3 = (v3, acceleration(p3, v3));
+        let (p4, v4) = (advance::<T>(position, k3.0, dt), advance::<T>(velocity, k3.1, dt));
+        let k4 = (v4, acceleration(p4, v4));
+
+        let weighted = |k1: T::Vector, k2: T::Vector, k3: T::Vector, k4: T::Vector|
+            T::scale(T::add(T::add(k1, T::scale(T::add(k2, k3), 2.0)), k4), 1.0 / 6.0);
+        node.moved(
+            advance::<T>(position, weighted(k1.0, k2.0, k3.0, k4.0), dt),
+            advance::<T>(velocity, weighted(k1.1, k2.1, k3.1, k4.1), dt),
+            dt)
+    }
+}
+
+impl<T: Kinematic> Mesh<T> {
+    // Moves every node in `nodes` with `integrator` at the start of each step, before the
+    // interconnectors are projected. Selfconnectors that `Kinematic::acceleration` recognises
+    // (gravity, and bound gravity above its boundary, for Verlet nodes) feed the integrator
+    // along with the mesh's damping and drag, and are only resolved afterwards when
+    // `Kinematic::still_resolves` says so (bound gravity's ground).
+    pub fn set_integrator(&mut self, integrator: Arc<dyn Integrator<T>>) {
+        self.integration = Some(Arc::new(move |mesh: &Mesh<T>, context: &StepContext|
+            mesh.integrate(integrator.as_ref(), context)));
+    }
+
+    // back to the relations doing their own integration
+    pub fn clear_integrator(&mut self) {
+        self.integration = None;
+    }
+
+    pub fn has_integrator(&self) -> bool {
+        self.integration.is_some()
+    }
+
+    fn integrate(&self, integrator: &dyn Integrator<T>, context: &StepContext) -> Result<Vec<bool>, NacError> {
+        let dt = context.dt;
+        let key = |node: &SharedNode<T>| Arc::as_ptr(node) as usize;
+        let in_mesh: HashSet<usize> = self.nodes.iter().map(key).collect();
+
+        let mut taken = vec![false; self.selfconnectors.len()];
+        let mut sources: HashMap<usize, Vec<&dyn SelfStepResolve<T>>> = HashMap::new();
+        for (idx, connector) in self.selfconnectors.iter().enumerate()
+            .filter(|(_idx, connector)| in_mesh.contains(&key(&connector.node)) && !self.holds_selfconnector(connector)) {
+            let relation = connector.relation().as_ref();
+            if T::acceleration(relation, &self.read_blamed(&connector.node)?.data, context).is_some() {
+                taken[idx] = !T::still_resolves(relation);
+                sources.entry(key(&connector.node)).or_default().push(relation);
+            }
+        }
+        if dt <= 0.0 {
+            return Ok(taken);
+        }
+
+        let mut seen = HashSet::new();
+        for shared in self.nodes.iter().filter(|node| seen.insert(key(node)) && !self.is_quarantined(node)) {
+            let relations = sources.get(&key(shared)).map(Vec::as_slice).unwrap_or(&[]);
+            let updated = {
+                let node = self.read_blamed(shared)?;
+                let acceleration = |position: T::Vector, velocity: T::Vector| {
+                    let trial = node.data.moved(position, velocity, dt);
+                    relations.iter()
+                        .filter_map(|relation| T::acceleration(*relation, &trial, context))
+                        .chain(trial.resistance(velocity, &context.damping))
+                        .fold(T::Vector::default(), T::add)
+                };
+                integrator.integrate(&node.data, &acceleration, dt)
+            };
+            self.write_blamed(shared)?.data = updated;
+        }
+        Ok(taken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nac::{Node, SelfConnector};
+    use crate::constraint::Constraint;
+    use crate::verlet::Verlet;
+
+    use std::sync::RwLock;
+
+    fn falling(constraint: Constraint) -> (Mesh<Verlet>, SharedNode<Verlet>) {
+        let node = Arc::new(RwLock::new(Node::new(Verlet::new(0.0, 10.0))));
+        let relation: Arc<dyn SelfStepResolve<Verlet>> = Arc::new(constraint);
+        let mut mesh = Mesh::new(vec!(Arc::clone(&node)), vec!(), vec!(SelfConnector::stepped("gravity", Arc::clone(&node), relation)));
+        mesh.set_integrator(Arc::new(SemiImplicitEuler));
+        (mesh, node)
+    }
+
+    #[test]
+    fn bound_gravity_falls_like_gravity_until_the_ground() {
+        let (mut free, free_node) = falling(Constraint::Gravity { gravity: -20.0, delta: None });
+        let (mut bound, bound_node) = falling(Constraint::BoundGravity { gravity: -20.0, boundary: 0.0, delta: None });
+        let y = |node: &SharedNode<Verlet>| node.read().unwrap().data.position.y;
+
+        // the acceleration is half of `gravity`: 10 units/s² fall 5 units in the first second
+        for _ in 0..10 {
+            free.update(0.1, 1).unwrap();
+            bound.update(0.1, 1).unwrap();
+            assert_eq!(y(&free_node), y(&bound_node));
+        }
+        assert!((y(&free_node) - 4.5).abs() < 1e-9);
+
+        // and rests on it rather than sinking through between clamps
+        for _ in 0..50 {
+            bound.update(0.1, 1).unwrap();
+            assert!(y(&bound_node) >= 0.0);
+        }
+        assert_eq!(y(&bound_node), 0.0);
+    }
+}
@@ -6,7 +6,22 @@
 #[macro_use]
 extern crate derive_builder;
 
+#[macro_use]
+extern crate slog;
+
 // nodes and connectors code
 pub mod nac;
+pub mod error;
+pub mod handle;
 pub mod verlet;
 pub mod cloth;
+pub mod snapshot;
+pub mod history;
+pub mod compose;
+pub mod integrator;
+pub mod constraint;
+pub mod scene;
+pub mod poison;
+pub mod logging;
+pub mod tear;
+pub mod damping;
@@ -0,0 +1,137 @@
+use crate::nac::{Node, Mesh, Residual};
+
+use slog::{Drain, Logger, Level, Record, OwnedKVList, KV, Key, Serializer, Never};
+
+use std::ffi::CString;
+use std::fmt::{self, Write};
+use std::os::raw::{c_char, c_void};
+
+// Events a mesh logs, by level:
+//   debug    step start and end, with the solver passes a step took
+//   info     connectors added or removed
+//   warning  corrections over the threshold of `log_corrections_over`, nodes found poisoned
+// Meshes log nowhere until given a logger.
+pub fn discard() -> Logger {
+    Logger::root(slog::Discard, o!())
+}
+
+// how a node's correction is measured when looking for large ones
+pub(crate) type CorrectionMeasure<T> = fn(&Node<T>, &Node<T>) -> f64;
+
+impl<T: Sync + Send + 'static> Mesh<T> {
+    pub fn set_logger(&mut self, logger: Logger) {
+        self.logger = logger;
+    }
+
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+}
+
+impl<T: Sync + Send + Residual + 'static> Mesh<T> {
+    // Warns about every interconnector that moves a node further than `threshold` in one
+    // pass. Each node's correction is measured even in plain updates once this is set.
+    pub fn log_corrections_over(&mut self, threshold: f64) {
+        self.large_correction = Some((threshold, |node: &Node<T>, updated: &Node<T>| node.data.residual(&updated.data)));
+    }
+
+    pub fn clear_correction_logging(&mut self) {
+        self.large_correction = None;
+    }
+}
+
+// Receives a mesh's log lines: the slog level (1 critical to 6 trace), the message
+// followed by its key=value pairs, and the user data it was registered with. The line is
+// only valid for the duration of the call. Parallel solvers call it from their workers.
+pub type LogCallback = unsafe extern "C" fn(u8, *const c_char, *mut c_void);
+
+// A drain handing each record to a C callback as one line of text
+pub struct CallbackDrain {
+    callback: LogCallback,
+    user_data: *mut c_void,
+}
+
+// whoever registers the callback vouches for calling it, and its user data, from any thread
+unsafe impl Send for CallbackDrain {}
+unsafe impl Sync for CallbackDrain {}
+
+impl CallbackDrain {
+    pub fn new(callback: LogCallback, user_data: *mut c_void) -> Self {
+        Self { callback, user_data }
+    }
+
+    // a logger through this drain that lets records up to `max_level` pass
+    pub fn logger(self, max_level: Level) -> Logger {
+        Logger::root(self.filter_level(max_level).fuse(), o!())
+    }
+}
+
+struct LineSerializer<'a>(&'a mut String);
+
+impl<'a> Serializer for LineSerializer<'a> {
+    fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> slog::Result {
+        write!(self.0, " {}={}", key, val)?;
+        Ok(())
+    }
+}
+
+impl Drain for CallbackDrain {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<(), Never> {
+        let mut line = record.msg().to_string();
+        // formatting into a String cannot fail
+        let _ = record.kv().serialize(record, &mut LineSerializer(&mut line));
+        let _ = values.serialize(record, &mut LineSerializer(&mut line));
+        if let Ok(line) = CString::new(line.replace('\0', "")) {
+            unsafe { (self.callback)(record.level().as_usize() as u8, line.as_ptr(), self.user_data) };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nac::{SharedNode, InterConnector};
+    use crate::constraint::Constraint;
+    use crate::verlet::Verlet;
+
+    use std::ffi::CStr;
+    use std::sync::{Arc, Mutex, RwLock};
+
+    unsafe extern "C" fn collect(level: u8, line: *const c_char, user_data: *mut c_void) {
+        let lines = &*(user_data as *const Mutex<Vec<(u8, String)>>);
+        lines.lock().unwrap().push((level, CStr::from_ptr(line).to_str().unwrap().to_owned()));
+    }
+
+    #[test]
+    fn steps_and_large_corrections_reach_the_callback() {
+        let nodes: Vec<SharedNode<Verlet>> = vec!(
+            Arc::new(RwLock::new(Node::new(Verlet::new_pinned(0.0, 0.0)))),
+            Arc::new(RwLock::new(Node::new(Verlet::new(2.0, 0.0)))));
+        let link = InterConnector::stepped(Arc::clone(&nodes[1]), Some(vec!(Arc::clone(&nodes[0]))),
+            Arc::new(Constraint::Distance { spacing: 1.0, spring: 0.5, tear: None })).with_name("link");
+        let mut mesh = Mesh::new(nodes, vec!(link), vec!());
+
+        let lines: Mutex<Vec<(u8, String)>> = Mutex::new(vec!());
+        mesh.set_logger(CallbackDrain::new(collect, &lines as *const _ as *mut c_void).logger(Level::Debug));
+        mesh.log_corrections_over(0.1);
+        mesh.update(0.016, 1).unwrap();
+        // the callback's user data has to outlive the logger
+        drop(mesh);
+
+        let lines = lines.into_inner().unwrap();
+        let find = |message: &str| lines.iter().find(|(_level, line)| line.starts_with(message)).unwrap_or_else(|| panic!("no {} in {:?}", message, lines));
+        let debug = Level::Debug.as_usize() as u8;
+        assert_eq!(find("step start").0, debug);
+        assert!(find("step start").1.contains(" dt=0.016"));
+        assert_eq!(find("step end").0, debug);
+        let (level, line) = find("large correction");
+        assert_eq!(*level, Level::Warning.as_usize() as u8);
+        for pair in [" correction=0.5", " threshold=0.1", " iteration=0", " name=link"] {
+            assert!(line.contains(pair), "{} has no{}", line, pair);
+        }
+    }
+}
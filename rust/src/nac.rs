@@ -1,6 +1,21 @@
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard, Mutex, MutexGuard, Barrier};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::any::Any;
+use std::collections::{HashSet, HashMap, BTreeSet};
 
 use std::thread;
+use std::panic;
+
+use crate::handle::{Slots, NodeHandle, InterHandle, SelfHandle};
+use crate::history::History;
+use crate::snapshot::Forks;
+use crate::error::NacError;
+use crate::poison::{PoisonPolicy, Poisoning};
+use crate::logging::{self, CorrectionMeasure};
+use crate::tear::OnBreak;
+use crate::damping::Damping;
+
+use slog::Logger;
 
 #[derive(Debug,Clone,Copy)]
 pub struct Node<T: Sync + Send + 'static> { pub data: T }
@@ -35,14 +50,252 @@ impl<T: Sync + Send + 'static, F> SelfResolve<T> for F where F: Send + Sync + 's
     }
 }
 
+// What a relation knows about the step it is resolving in.
+// `iteration` counts solver passes over the interconnectors and is 0 for selfconnectors.
+// `integrated` is set when the mesh's integrator already moved the nodes this step, so
+// relations must not add inertia of their own. `damping` is the mesh's, for relations that
+// carry velocity into the next step.
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+pub struct StepContext {
+    pub dt: f64,
+    pub elapsed: f64,
+    pub iteration: u8,
+    pub integrated: bool,
+    pub damping: Damping,
+}
+
+// Closures get this for free. Relations that keep their parameters as data (see
+// `constraint::Constraint`) also return themselves from `as_any`, so connectors can be
+// inspected and edited after they are built.
+pub trait InterStepResolve<T: Sync + Send + 'static>: Send + Sync + 'static {
+    fn resolve(&self, primary_node: &Node<T>, secondary_node: &Node<T>, context: &StepContext) -> (Node<T>, Node<T>);
+
+    // Asked after every step; true tears the connector out of the mesh (see Mesh::set_break_callback).
+    fn breaks(&self, _primary_node: &Node<T>, _secondary_node: &Node<T>) -> bool {
+        false
+    }
+
+    // How far the nodes are from satisfying the relation, which converging solvers measure
+    // after each pass. Without one they measure how far another resolve would move the nodes.
+    fn error(&self, _primary_node: &Node<T>, _secondary_node: &Node<T>) -> Option<f64> {
+        None
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
+}
+
+impl<T: Sync + Send + 'static, F> InterStepResolve<T> for F where F: Send + Sync + 'static + Fn(&Node<T>, &Node<T>, &StepContext) -> (Node<T>,Node<T>) {
+    fn resolve(&self, primary_node: &Node<T>, secondary_node: &Node<T>, context: &StepContext) -> (Node<T>, Node<T>) {
+        self(primary_node, secondary_node, context)
+    }
+}
+
+pub trait SelfStepResolve<T: Sync + Send + 'static>: Send + Sync + 'static {
+    fn resolve(&self, node: &Node<T>, context: &StepContext) -> Node<T>;
+
+    // A copy with its own state (such as a wind generator's place in its sequence), for
+    // snapshots and the meshes built from them. Relations that only hold parameters return
+    // None and are shared instead.
+    fn fork(&self, _forks: &mut Forks) -> Option<Arc<dyn SelfStepResolve<T>>> {
+        None
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
+}
+
+impl<T: Sync + Send + 'static, F> SelfStepResolve<T> for F where F: Send + Sync + 'static + Fn(&Node<T>, &StepContext) -> Node<T> {
+    fn resolve(&self, node: &Node<T>, context: &StepContext) -> Node<T> {
+        self(node, context)
+    }
+}
+
+// Sees every node of a connector at once: the primary node first, then its constraints in
+// order. Returns the updated nodes in the same order, for constraints such as angles or
+// areas that cannot be split into pairs.
+pub trait GroupStepResolve<T: Sync + Send + 'static>: Send + Sync + 'static {
+    fn resolve(&self, nodes: &[&Node<T>], context: &StepContext) -> Vec<Node<T>>;
+
+    fn breaks(&self, _nodes: &[&Node<T>]) -> bool {
+        false
+    }
+
+    fn error(&self, _nodes: &[&Node<T>]) -> Option<f64> {
+        None
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
+}
+
+impl<T: Sync + Send + 'static, F> GroupStepResolve<T> for F where F: Send + Sync + 'static + Fn(&[&Node<T>], &StepContext) -> Vec<Node<T>> {
+    fn resolve(&self, nodes: &[&Node<T>], context: &StepContext) -> Vec<Node<T>> {
+        self(nodes, context)
+    }
+}
+
+// How far a relation moved a node. Converging solvers judge relations that cannot report
+// their own error by how far resolving them again would move their nodes.
+pub trait Residual {
+    fn residual(&self, updated: &Self) -> f64;
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ResidualNorm {
+    Max,
+    Rms,
+}
+
+// Running max and sum of squares of the corrections made during one solver pass,
+// or of the constraint errors it left
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+pub struct Residuals {
+    pub max: f64,
+    pub sum_squares: f64,
+    pub count: usize,
+}
+
+impl Residuals {
+    pub fn record(&mut self, correction: f64) {
+        self.max = self.max.max(correction);
+        self.sum_squares += correction.powi(2);
+        self.count += 1;
+    }
+
+    pub fn merge(&mut self, other: Residuals) {
+        self.max = self.max.max(other.max);
+        self.sum_squares += other.sum_squares;
+        self.count += other.count;
+    }
+
+    pub fn norm(&self, norm: ResidualNorm) -> f64 {
+        match norm {
+            ResidualNorm::Max => self.max,
+            ResidualNorm::Rms if self.count > 0 => (self.sum_squares / self.count as f64).sqrt(),
+            ResidualNorm::Rms => 0.0,
+        }
+    }
+}
+
+// Iterate until no interconnector is off by more than `tolerance` after a pass, or `max_iterations` passes ran
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct Convergence {
+    pub tolerance: f64,
+    pub max_iterations: u8,
+    pub norm: ResidualNorm,
+}
+
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+#[repr(C)]
+pub struct SolveReport {
+    pub iterations: u8,
+    pub residual: f64,
+}
+
+// Lets the Jacobi solver sum the corrections relations make to one node and apply their average.
+pub trait JacobiCorrection: Sized {
+    type Correction: Copy + Default;
+    fn correction(&self, updated: &Self) -> Self::Correction;
+    fn accumulate(total: Self::Correction, correction: Self::Correction) -> Self::Correction;
+    fn corrected(&self, total: Self::Correction, count: usize) -> Self;
+}
+
+// Node data an Integrator can advance, as a position and a velocity.
+pub trait Kinematic: Sized + Sync + Send + 'static {
+    type Vector: Copy + Default;
+    fn position(&self) -> Self::Vector;
+    // velocity over a step of length dt, or over the node's last step if it knows how long that took
+    fn velocity(&self, dt: f64) -> Self::Vector;
+    // the same node moved to `position` having travelled at `velocity` over dt; pinned nodes stay put
+    fn moved(&self, position: Self::Vector, velocity: Self::Vector, dt: f64) -> Self;
+    fn add(a: Self::Vector, b: Self::Vector) -> Self::Vector;
+    fn scale(a: Self::Vector, by: f64) -> Self::Vector;
+    // The acceleration a self relation stands for, if the integrator should apply it in place
+    // of resolving the relation. Called with the node at whatever trial state is being evaluated.
+    fn acceleration(relation: &dyn SelfStepResolve<Self>, node: &Self, context: &StepContext) -> Option<Self::Vector>;
+    // Whether a relation `acceleration` recognises is still resolved once the integrator has
+    // moved the node, for what it does besides accelerating it (such as holding up a ground)
+    fn still_resolves(_relation: &dyn SelfStepResolve<Self>) -> bool {
+        false
+    }
+    // The acceleration damping and drag put on the node moving at `velocity`, opposing it
+    fn resistance(&self, _velocity: Self::Vector, _damping: &Damping) -> Option<Self::Vector> {
+        None
+    }
+}
+
+// Advances a node over dt under `acceleration`, a function of position and velocity that
+// an integrator may sample as often as it likes.
+pub trait Integrator<T: Kinematic>: Send + Sync + 'static {
+    fn integrate(&self, node: &T, acceleration: &dyn Fn(T::Vector, T::Vector) -> T::Vector, dt: f64) -> T;
+}
+
+// integrates the mesh nodes, returning which selfconnectors it took over
+pub(crate) type IntegrationPass<T> = Arc<dyn Fn(&Mesh<T>, &StepContext) -> Result<Vec<bool>, NacError> + Send + Sync>;
+
+pub(crate) type JacobiPass<T> = fn(&Mesh<T>, &StepContext, &(dyn Fn(&Node<T>, &Node<T>) -> f64 + Sync)) -> Result<Residuals, NacError>;
+
 pub type SharedNode<T> = Arc<RwLock<Node<T>>>;
 pub type SharedNodes<T> = Vec<SharedNode<T>>;
 
+// node locks, with poisoning reported rather than unwrapped
+pub fn read_node<T: Sync + Send + 'static>(node: &SharedNode<T>) -> Result<RwLockReadGuard<'_, Node<T>>, NacError> {
+    node.read().map_err(|_| NacError::PoisonedNode(Poisoning::default()))
+}
+
+pub fn write_node<T: Sync + Send + 'static>(node: &SharedNode<T>) -> Result<RwLockWriteGuard<'_, Node<T>>, NacError> {
+    node.write().map_err(|_| NacError::PoisonedNode(Poisoning::default()))
+}
+
+// How an interconnector resolves: against each of its constraints in turn, or all at once
+pub enum InterRelation<T: Sync + Send + 'static> {
+    Pairwise(Arc<dyn InterStepResolve<T>>),
+    Group(Arc<dyn GroupStepResolve<T>>),
+}
+
+impl<T: Sync + Send + 'static> InterRelation<T> {
+    pub fn as_any(&self) -> Option<&dyn Any> {
+        match self {
+            InterRelation::Pairwise(relation) => relation.as_any(),
+            InterRelation::Group(relation) => relation.as_any(),
+        }
+    }
+}
+
+impl<T: Sync + Send + 'static> Clone for InterRelation<T> {
+    fn clone(&self) -> Self {
+        match self {
+            InterRelation::Pairwise(relation) => InterRelation::Pairwise(Arc::clone(relation)),
+            InterRelation::Group(relation) => InterRelation::Group(Arc::clone(relation)),
+        }
+    }
+}
+
+impl<T: Sync + Send + 'static> From<Arc<dyn InterStepResolve<T>>> for InterRelation<T> {
+    fn from(relation: Arc<dyn InterStepResolve<T>>) -> Self {
+        InterRelation::Pairwise(relation)
+    }
+}
+
+impl<T: Sync + Send + 'static> From<Arc<dyn GroupStepResolve<T>>> for InterRelation<T> {
+    fn from(relation: Arc<dyn GroupStepResolve<T>>) -> Self {
+        InterRelation::Group(relation)
+    }
+}
+
 #[derive(Clone)]
 pub struct InterConnector<T: Sync + Send + 'static> {
+    pub name: String,
+    pub tags: BTreeSet<String>,
+    // disabled connectors stay in the mesh but are skipped when resolving
+    pub enabled: bool,
     pub node: SharedNode<T>,
     pub constraints: Option<SharedNodes<T>>,
-    relation: Arc<dyn InterResolve<T> + 'static>,
+    relation: InterRelation<T>,
     // hashmap for custom properties depending on trait?
 }
 
@@ -50,44 +303,137 @@ pub struct InterConnector<T: Sync + Send + 'static> {
 pub struct SelfConnector<T: Sync + Send + 'static> {
     pub name: String,
     pub node: SharedNode<T>,
-    relation: Arc<dyn SelfResolve<T> + 'static>,
+    relation: Arc<dyn SelfStepResolve<T> + 'static>,
 }
 
 
 // A connector can connect a node with other node(s) with a specific constraint function
 impl<T: Sync + Send + 'static> InterConnector<T> {
     pub fn new(node: SharedNode<T>, constraints: Option<SharedNodes<T>>, relation: Arc<dyn InterResolve<T>>) -> Self {
+        let relation = move |primary_node: &Node<T>, secondary_node: &Node<T>, _context: &StepContext|
+            relation(primary_node, secondary_node);
+        Self::stepped(node, constraints, Arc::new(relation))
+    }
+
+    pub fn stepped(node: SharedNode<T>, constraints: Option<SharedNodes<T>>, relation: Arc<dyn InterStepResolve<T>>) -> Self {
+        Self::with_relation(node, constraints, InterRelation::Pairwise(relation))
+    }
+
+    pub fn group(node: SharedNode<T>, constraints: SharedNodes<T>, relation: Arc<dyn GroupStepResolve<T>>) -> Self {
+        Self::with_relation(node, Some(constraints), InterRelation::Group(relation))
+    }
+
+    pub fn with_relation(node: SharedNode<T>, constraints: Option<SharedNodes<T>>, relation: InterRelation<T>) -> Self {
         Self {
+            name: String::new(),
+            tags: BTreeSet::new(),
+            enabled: true,
             node,
             constraints,
             relation
         }
     }
 
-    pub fn resolve(&self) {
-        let resolver_relation = &self.relation;
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_owned();
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.insert(tag.to_owned());
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    pub fn relation(&self) -> &InterRelation<T> {
+        &self.relation
+    }
+
+    pub fn set_relation<R: Into<InterRelation<T>>>(&mut self, relation: R) {
+        self.relation = relation.into();
+    }
+
+    // the relation as `C`, if it was built from one rather than a closure
+    pub fn constraint<C: 'static>(&self) -> Option<&C> {
+        self.relation.as_any().and_then(|relation| relation.downcast_ref::<C>())
+    }
+
+    // Edits a copy of the connector's `C` constraint and swaps it in, so whatever still holds
+    // the old relation (such as a snapshot) keeps the old parameters. `edit` says
+    // whether it changed anything; closures and other constraint types are left alone.
+    pub fn edit_constraint<C, F>(&mut self, edit: F) -> bool
+        where C: InterStepResolve<T> + Clone, F: FnOnce(&mut C) -> bool
+    {
+        let mut constraint = match (&self.relation, self.constraint::<C>()) {
+            (InterRelation::Pairwise(_), Some(constraint)) => constraint.clone(),
+            _ => return false,
+        };
+        if edit(&mut constraint) {
+            self.relation = InterRelation::Pairwise(Arc::new(constraint));
+            true
+        } else {
+            false
+        }
+    }
+
+    // the same for connectors resolving their nodes as a group
+    pub fn edit_group_constraint<C, F>(&mut self, edit: F) -> bool
+        where C: GroupStepResolve<T> + Clone, F: FnOnce(&mut C) -> bool
+    {
+        let mut constraint = match (&self.relation, self.constraint::<C>()) {
+            (InterRelation::Group(_), Some(constraint)) => constraint.clone(),
+            _ => return false,
+        };
+        if edit(&mut constraint) {
+            self.relation = InterRelation::Group(Arc::new(constraint));
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn resolve(&self, context: &StepContext) -> Result<(), NacError> {
+        self.resolve_measured(context, &|_node: &Node<T>, _updated: &Node<T>| 0.0, &mut Residuals::default())
+    }
+
+    // resolve, recording how far `measure` says each node was corrected
+    pub fn resolve_measured<M>(&self, context: &StepContext, measure: &M, residuals: &mut Residuals) -> Result<(), NacError>
+        where M: Fn(&Node<T>, &Node<T>) -> f64
+    {
+        if !self.enabled {
+            return Ok(());
+        }
+        let resolver_relation = match &self.relation {
+            InterRelation::Pairwise(relation) => relation,
+            InterRelation::Group(relation) => return self.resolve_group(relation.as_ref(), context, measure, residuals),
+        };
         if let Some(constraints) = &self.constraints {
-            constraints.iter().for_each(
-                |constraint| {
-                    let (updated_node, updated_constraint) = resolver_relation(&self.node.read().unwrap(), &constraint.read().unwrap());
-                    if let Ok(mut node) = self.node.write() {
-                        *node = updated_node
-                    } else {
-                        panic!("Interconnector node update failed");
-                    }
-                    if let Ok(mut contraint) = constraint.write() {
-                        *contraint = updated_constraint
-                    } else {
-                        panic!("Interconnector constraint update failed");
-                    }
-                }
-            );
+            for constraint in constraints {
+                let (updated_node, updated_constraint) = {
+                    let (node, constraint) = (read_node(&self.node)?, read_node(constraint)?);
+                    let (updated_node, updated_constraint) = resolver_relation.resolve(&node, &constraint, context);
+                    residuals.record(measure(&node, &updated_node));
+                    residuals.record(measure(&constraint, &updated_constraint));
+                    (updated_node, updated_constraint)
+                };
+                *write_node(&self.node)? = updated_node;
+                *write_node(constraint)? = updated_constraint;
+            }
         }
+        Ok(())
     }
 }
 
 impl<T: Sync + Send + 'static> SelfConnector<T> {
     pub fn new(name: &str, node: SharedNode<T>, relation: Arc<dyn SelfResolve<T>>) -> Self {
+        let relation = move |node: &Node<T>, _context: &StepContext| relation(node);
+        Self::stepped(name, node, Arc::new(relation))
+    }
+
+    pub fn stepped(name: &str, node: SharedNode<T>, relation: Arc<dyn SelfStepResolve<T>>) -> Self {
         Self {
             name: name.to_owned(),
             node,
@@ -95,42 +441,1051 @@ impl<T: Sync + Send + 'static> SelfConnector<T> {
         }
     }
 
-    pub fn resolve(&self) {
-        let resolver_relation = &self.relation;
-        let updated_node = resolver_relation(&self.node.read().unwrap());
-        if let Ok(mut node) = self.node.write() {
-            *node = updated_node
+    pub fn relation(&self) -> &Arc<dyn SelfStepResolve<T>> {
+        &self.relation
+    }
+
+    pub fn set_relation(&mut self, relation: Arc<dyn SelfStepResolve<T>>) {
+        self.relation = relation;
+    }
+
+    pub fn constraint<C: 'static>(&self) -> Option<&C> {
+        self.relation.as_any().and_then(|relation| relation.downcast_ref::<C>())
+    }
+
+    pub fn edit_constraint<C, F>(&mut self, edit: F) -> bool
+        where C: SelfStepResolve<T> + Clone, F: FnOnce(&mut C) -> bool
+    {
+        let mut constraint = match self.constraint::<C>() {
+            Some(constraint) => constraint.clone(),
+            None => return false,
+        };
+        if edit(&mut constraint) {
+            self.relation = Arc::new(constraint);
+            true
         } else {
-            panic!{"Selfconnector node resolve failed"}
+            false
         }
     }
+
+    pub fn resolve(&self, context: &StepContext) -> Result<(), NacError> {
+        let resolver_relation = &self.relation;
+        let updated_node = resolver_relation.resolve(&*read_node(&self.node)?, context);
+        *write_node(&self.node)? = updated_node;
+        Ok(())
+    }
 }
 
 
 
+impl<T: Sync + Send + 'static> InterConnector<T> {
+    // the primary node followed by the constraints, the order group relations see them in
+    pub fn members(&self) -> Vec<&SharedNode<T>> {
+        let mut members = vec!(&self.node);
+        if let Some(constraints) = &self.constraints {
+            members.extend(constraints.iter());
+        }
+        members
+    }
+
+    fn resolve_group<M>(&self, relation: &dyn GroupStepResolve<T>, context: &StepContext, measure: &M, residuals: &mut Residuals) -> Result<(), NacError>
+        where M: Fn(&Node<T>, &Node<T>) -> f64
+    {
+        let members = self.members();
+        let updated = {
+            let guards = members.iter().map(|member| read_node(member)).collect::<Result<Vec<_>, _>>()?;
+            let nodes: Vec<&Node<T>> = guards.iter().map(|guard| &**guard).collect();
+            let updated = relation.resolve(&nodes, context);
+            nodes.iter().zip(updated.iter()).for_each(|(node, updated_node)|
+                residuals.record(measure(node, updated_node)));
+            updated
+        };
+        for (member, updated_node) in members.into_iter().zip(updated) {
+            *write_node(member)? = updated_node;
+        }
+        Ok(())
+    }
+
+    // Records the error the relation reports for each constraint, or for the group; relations
+    // without one record how far `measure` says resolving again would move the nodes.
+    pub fn measure_error<M>(&self, context: &StepContext, measure: &M, residuals: &mut Residuals) -> Result<(), NacError>
+        where M: Fn(&Node<T>, &Node<T>) -> f64
+    {
+        if !self.enabled {
+            return Ok(());
+        }
+        match &self.relation {
+            InterRelation::Pairwise(relation) => for constraint in self.constraints.iter().flatten() {
+                let (node, constraint) = (read_node(&self.node)?, read_node(constraint)?);
+                match relation.error(&node, &constraint) {
+                    Some(error) => residuals.record(error),
+                    None => {
+                        let (updated_node, updated_constraint) = relation.resolve(&node, &constraint, context);
+                        residuals.record(measure(&node, &updated_node));
+                        residuals.record(measure(&constraint, &updated_constraint));
+                    },
+                }
+            },
+            InterRelation::Group(relation) => {
+                let members = self.members();
+                let guards = members.iter().map(|member| read_node(member)).collect::<Result<Vec<_>, _>>()?;
+                let nodes: Vec<&Node<T>> = guards.iter().map(|guard| &**guard).collect();
+                match relation.error(&nodes) {
+                    Some(error) => residuals.record(error),
+                    None => nodes.iter().zip(relation.resolve(&nodes, context).iter())
+                        .for_each(|(node, updated_node)| residuals.record(measure(node, updated_node))),
+                }
+            },
+        }
+        Ok(())
+    }
+
+    // every node this connector reads or writes, keyed by lock address
+    fn node_keys(&self) -> Vec<usize> {
+        self.members().into_iter().map(|member| Arc::as_ptr(member) as usize).collect()
+    }
+
+    // whether the relation says the connector has torn, between the primary node and any constraint
+    pub fn is_broken(&self) -> Result<bool, NacError> {
+        match &self.relation {
+            InterRelation::Pairwise(relation) => {
+                let node = read_node(&self.node)?;
+                for constraint in self.constraints.iter().flatten() {
+                    if relation.breaks(&node, &*read_node(constraint)?) {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            },
+            InterRelation::Group(relation) => {
+                let members = self.members();
+                let guards = members.iter().map(|member| read_node(member)).collect::<Result<Vec<_>, _>>()?;
+                let nodes: Vec<&Node<T>> = guards.iter().map(|guard| &**guard).collect();
+                Ok(relation.breaks(&nodes))
+            },
+        }
+    }
+
+    pub fn touches(&self, node: &SharedNode<T>) -> bool {
+        Arc::ptr_eq(&self.node, node) || match &self.constraints {
+            Some(constraints) => constraints.iter().any(|constraint| Arc::ptr_eq(constraint, node)),
+            None => false,
+        }
+    }
+}
+
+// groups smaller than this are not worth handing to worker threads
+const PARALLEL_MIN_GROUP: usize = 64;
+
+enum WorkerFailure {
+    Error(NacError),
+    Panic(Box<dyn Any + Send>),
+}
+
+// the solver's own locks are never held across a relation, so poisoning cannot leave them torn
+fn locked<V>(mutex: &Mutex<V>) -> MutexGuard<'_, V> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn converged(convergence: Option<Convergence>, report: &SolveReport) -> bool {
+    convergence.is_some_and(|convergence| report.residual <= convergence.tolerance)
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Solver {
+    // resolve interconnectors one after another in insertion order
+    Serial,
+    // resolve independent colour groups of interconnectors across worker threads
+    Parallel { threads: usize },
+    // every interconnector sees the same node state; corrections are averaged per node
+    // and applied at the end of each pass, so push order no longer biases the result.
+    // Enabled through Mesh::enable_jacobi, which installs the pass it needs
+    Jacobi,
+}
+
+// Splits variable frame times into fixed-size substeps, carrying leftover time
+// into the next call so the simulation neither explodes nor slows down.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct FixedStep {
+    pub delta: f64,
+    // substeps allowed per advance; time beyond that is dropped rather than owed
+    pub max_substeps: u32,
+    pub physics_accuracy: u8,
+    accumulator: f64,
+}
+
+impl FixedStep {
+    pub fn new(delta: f64, max_substeps: u32, physics_accuracy: u8) -> Self {
+        Self {
+            delta,
+            max_substeps,
+            physics_accuracy,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn accumulator(&self) -> f64 {
+        self.accumulator
+    }
+
+    // how far between the last two substeps the leftover time lands
+    pub fn alpha(&self) -> f64 {
+        if self.delta > 0.0 {
+            self.accumulator / self.delta
+        } else {
+            0.0
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+
+    // queue frame time and return how many substeps to run now
+    fn substeps(&mut self, frame_time: f64) -> u32 {
+        if self.delta <= 0.0 {
+            return 0;
+        }
+        self.accumulator += frame_time.max(0.0);
+        let mut substeps = 0;
+        while self.accumulator >= self.delta && substeps < self.max_substeps {
+            self.accumulator -= self.delta;
+            substeps += 1;
+        }
+        if self.accumulator >= self.delta {
+            self.accumulator %= self.delta;
+        }
+        substeps
+    }
+}
+
+impl Default for FixedStep {
+    fn default() -> Self {
+        FixedStep::new(1.0 / 60.0, 4, 3)
+    }
+}
+
 pub struct Mesh<T: Sync + Send + 'static> {
     pub nodes: Vec<SharedNode<T>>,
     pub interconnectors: Vec<InterConnector<T>>,
     pub selfconnectors: Vec<SelfConnector<T>>,
+    pub solver: Solver,
+    // simulated time advanced by every update
+    pub elapsed: f64,
+    pub fixed_step: FixedStep,
+    pub(crate) jacobi: Option<JacobiPass<T>>,
+    pub(crate) integration: Option<IntegrationPass<T>>,
+    pub(crate) history: Option<History<T>>,
+    pub(crate) poison_policy: PoisonPolicy,
+    // quarantined nodes by address, they need not be mesh nodes
+    pub(crate) quarantine: HashMap<usize, SharedNode<T>>,
+    pub(crate) poisonings: Vec<Poisoning>,
+    pub(crate) logger: Logger,
+    pub(crate) large_correction: Option<(f64, CorrectionMeasure<T>)>,
+    pub(crate) on_break: Option<OnBreak>,
+    pub(crate) damping: Damping,
+    // colour groups of the interconnectors, dropped whenever one is added or removed
+    pub(crate) colouring: Option<Arc<Vec<Vec<usize>>>>,
+    pub(crate) node_slots: Slots<NodeHandle>,
+    pub(crate) interconnector_slots: Slots<InterHandle>,
+    pub(crate) selfconnector_slots: Slots<SelfHandle>,
 }
 
 impl<T: Sync + Send + 'static> Mesh<T> {
     pub fn new(nodes: Vec<SharedNode<T>>, interconnectors: Vec<InterConnector<T>>, selfconnectors: Vec<SelfConnector<T>>) -> Self {
-        Mesh {
+        let mut mesh = Mesh {
             nodes,
             interconnectors,
             selfconnectors,
+            solver: Solver::Serial,
+            elapsed: 0.0,
+            fixed_step: FixedStep::default(),
+            jacobi: None,
+            integration: None,
+            history: None,
+            poison_policy: PoisonPolicy::default(),
+            quarantine: HashMap::new(),
+            poisonings: Vec::new(),
+            logger: logging::discard(),
+            large_correction: None,
+            on_break: None,
+            damping: Damping::default(),
+            colouring: None,
+            node_slots: Slots::new(),
+            interconnector_slots: Slots::new(),
+            selfconnector_slots: Slots::new(),
+        };
+        mesh.sync_handles();
+        mesh
+    }
+
+    // entries pushed straight onto the pub Vecs get their handles here;
+    // removing from the Vecs directly invalidates handle lookups, use the remove_* methods
+    pub fn sync_handles(&mut self) {
+        if self.interconnector_slots.len() != self.interconnectors.len() {
+            self.colouring = None;
+        }
+        self.node_slots.sync(self.nodes.len());
+        self.interconnector_slots.sync(self.interconnectors.len());
+        self.selfconnector_slots.sync(self.selfconnectors.len());
+    }
+
+    pub fn add_node(&mut self, node: SharedNode<T>) -> NodeHandle {
+        self.sync_handles();
+        self.nodes.push(node);
+        self.node_slots.push()
+    }
+
+    pub fn add_interconnector(&mut self, connector: InterConnector<T>) -> InterHandle {
+        self.sync_handles();
+        self.interconnectors.push(connector);
+        self.colouring = None;
+        let handle = self.interconnector_slots.push();
+        info!(self.logger, "interconnector added"; "slot" => handle.slot, "generation" => handle.generation,
+            "name" => &self.interconnectors[self.interconnectors.len() - 1].name);
+        handle
+    }
+
+    pub fn add_selfconnector(&mut self, connector: SelfConnector<T>) -> SelfHandle {
+        self.sync_handles();
+        self.selfconnectors.push(connector);
+        let handle = self.selfconnector_slots.push();
+        info!(self.logger, "selfconnector added"; "slot" => handle.slot, "generation" => handle.generation,
+            "name" => &self.selfconnectors[self.selfconnectors.len() - 1].name);
+        handle
+    }
+
+    // Mesh.nodes, then the nodes only reachable through connectors, each once and in the
+    // order a snapshot indexes them
+    pub fn reachable_nodes(&self) -> Vec<SharedNode<T>> {
+        let mut seen = HashSet::with_capacity(self.nodes.len());
+        let connected = self.interconnectors.iter().flat_map(InterConnector::members)
+            .chain(self.selfconnectors.iter().map(|connector| &connector.node));
+        self.nodes.iter().chain(connected)
+            .filter(|node| seen.insert(Arc::as_ptr(node) as usize))
+            .cloned()
+            .collect()
+    }
+
+    pub fn node_handle(&self, idx: usize) -> Option<NodeHandle> {
+        self.node_slots.handle(idx)
+    }
+
+    pub fn interconnector_handle(&self, idx: usize) -> Option<InterHandle> {
+        self.interconnector_slots.handle(idx)
+    }
+
+    pub fn selfconnector_handle(&self, idx: usize) -> Option<SelfHandle> {
+        self.selfconnector_slots.handle(idx)
+    }
+
+    pub fn node_index(&self, handle: NodeHandle) -> Option<usize> {
+        self.node_slots.index(handle)
+    }
+
+    pub fn interconnector_index(&self, handle: InterHandle) -> Option<usize> {
+        self.interconnector_slots.index(handle)
+    }
+
+    pub fn selfconnector_index(&self, handle: SelfHandle) -> Option<usize> {
+        self.selfconnector_slots.index(handle)
+    }
+
+    pub fn node(&self, handle: NodeHandle) -> Option<&SharedNode<T>> {
+        self.node_index(handle).map(|idx| &self.nodes[idx])
+    }
+
+    pub fn interconnector(&self, handle: InterHandle) -> Option<&InterConnector<T>> {
+        self.interconnector_index(handle).map(|idx| &self.interconnectors[idx])
+    }
+
+    pub fn selfconnector(&self, handle: SelfHandle) -> Option<&SelfConnector<T>> {
+        self.selfconnector_index(handle).map(|idx| &self.selfconnectors[idx])
+    }
+
+    // Removes the node along with every connector that touches it. Like every removal,
+    // this moves the last entry of each Vec into the removed one's place.
+    pub fn remove_node(&mut self, handle: NodeHandle) -> Option<SharedNode<T>> {
+        self.remove_nodes(&[handle]).pop()
+    }
+
+    // removes the nodes in one sweep over the connectors, returning them in mesh order;
+    // stale handles are skipped
+    pub fn remove_nodes(&mut self, handles: &[NodeHandle]) -> Vec<SharedNode<T>> {
+        self.sync_handles();
+        let mut indices: Vec<usize> = handles.iter().filter_map(|&handle| self.node_slots.index(handle)).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        let mut removed: Vec<SharedNode<T>> = indices.into_iter().rev().map(|idx| {
+            self.node_slots.remove_at(idx);
+            self.nodes.swap_remove(idx)
+        }).collect();
+        removed.reverse();
+
+        let keys: HashSet<usize> = removed.iter().map(|node| Arc::as_ptr(node) as usize).collect();
+        (0..self.interconnectors.len()).rev()
+            .filter(|&idx| self.interconnectors[idx].node_keys().iter().any(|key| keys.contains(key)))
+            .collect::<Vec<usize>>()
+            .into_iter()
+            .for_each(|idx| { self.remove_interconnector_at(idx); });
+        (0..self.selfconnectors.len()).rev()
+            .filter(|&idx| keys.contains(&(Arc::as_ptr(&self.selfconnectors[idx].node) as usize)))
+            .collect::<Vec<usize>>()
+            .into_iter()
+            .for_each(|idx| { self.remove_selfconnector_at(idx); });
+
+        removed
+    }
+
+    pub fn remove_interconnector(&mut self, handle: InterHandle) -> Option<InterConnector<T>> {
+        self.sync_handles();
+        let idx = self.interconnector_slots.index(handle)?;
+        Some(self.remove_interconnector_at(idx))
+    }
+
+    pub fn remove_selfconnector(&mut self, handle: SelfHandle) -> Option<SelfConnector<T>> {
+        self.sync_handles();
+        let idx = self.selfconnector_slots.index(handle)?;
+        Some(self.remove_selfconnector_at(idx))
+    }
+
+    pub fn remove_selfconnectors_by_name(&mut self, name: &str) -> usize {
+        self.sync_handles();
+        let matched: Vec<usize> = (0..self.selfconnectors.len()).rev()
+            .filter(|&idx| self.selfconnectors[idx].name == name)
+            .collect();
+        matched.iter().for_each(|&idx| { self.remove_selfconnector_at(idx); });
+        matched.len()
+    }
+
+    pub fn interconnectors_by_name(&self, name: &str) -> Vec<InterHandle> {
+        self.matching_interconnectors(|connector| connector.name == name).into_iter()
+            .filter_map(|idx| self.interconnector_handle(idx))
+            .collect()
+    }
+
+    pub fn interconnectors_by_tag(&self, tag: &str) -> Vec<InterHandle> {
+        self.matching_interconnectors(|connector| connector.has_tag(tag)).into_iter()
+            .filter_map(|idx| self.interconnector_handle(idx))
+            .collect()
+    }
+
+    pub fn set_interconnectors_enabled_by_name(&mut self, name: &str, enabled: bool) -> usize {
+        let matched = self.matching_interconnectors(|connector| connector.name == name);
+        matched.iter().for_each(|&idx| self.interconnectors[idx].enabled = enabled);
+        matched.len()
+    }
+
+    pub fn set_interconnectors_enabled_by_tag(&mut self, tag: &str, enabled: bool) -> usize {
+        let matched = self.matching_interconnectors(|connector| connector.has_tag(tag));
+        matched.iter().for_each(|&idx| self.interconnectors[idx].enabled = enabled);
+        matched.len()
+    }
+
+    pub fn remove_interconnectors_by_name(&mut self, name: &str) -> usize {
+        self.sync_handles();
+        let matched = self.matching_interconnectors(|connector| connector.name == name);
+        matched.iter().rev().for_each(|&idx| { self.remove_interconnector_at(idx); });
+        matched.len()
+    }
+
+    pub fn remove_interconnectors_by_tag(&mut self, tag: &str) -> usize {
+        self.sync_handles();
+        let matched = self.matching_interconnectors(|connector| connector.has_tag(tag));
+        matched.iter().rev().for_each(|&idx| { self.remove_interconnector_at(idx); });
+        matched.len()
+    }
+
+    pub fn edit_interconnector<C, F>(&mut self, handle: InterHandle, edit: F) -> bool
+        where C: InterStepResolve<T> + Clone, F: FnOnce(&mut C) -> bool
+    {
+        match self.interconnector_index(handle) {
+            Some(idx) => self.interconnectors[idx].edit_constraint(edit),
+            None => false,
         }
     }
 
-    pub fn update(&self, delta: f64, physics_accuracy: u8) {
-        (0..physics_accuracy).for_each(|_i| {
-            self.interconnectors.iter().for_each(|connector|
-                connector.resolve());
+    // returns how many connectors `edit` changed
+    pub fn edit_interconnectors_by_name<C, F>(&mut self, name: &str, mut edit: F) -> usize
+        where C: InterStepResolve<T> + Clone, F: FnMut(&mut C) -> bool
+    {
+        let matched = self.matching_interconnectors(|connector| connector.name == name);
+        matched.iter().filter(|&&idx| self.interconnectors[idx].edit_constraint(&mut edit)).count()
+    }
+
+    pub fn edit_interconnectors_by_tag<C, F>(&mut self, tag: &str, mut edit: F) -> usize
+        where C: InterStepResolve<T> + Clone, F: FnMut(&mut C) -> bool
+    {
+        let matched = self.matching_interconnectors(|connector| connector.has_tag(tag));
+        matched.iter().filter(|&&idx| self.interconnectors[idx].edit_constraint(&mut edit)).count()
+    }
+
+    pub fn edit_group_interconnector<C, F>(&mut self, handle: InterHandle, edit: F) -> bool
+        where C: GroupStepResolve<T> + Clone, F: FnOnce(&mut C) -> bool
+    {
+        match self.interconnector_index(handle) {
+            Some(idx) => self.interconnectors[idx].edit_group_constraint(edit),
+            None => false,
+        }
+    }
+
+    pub fn edit_group_interconnectors_by_name<C, F>(&mut self, name: &str, mut edit: F) -> usize
+        where C: GroupStepResolve<T> + Clone, F: FnMut(&mut C) -> bool
+    {
+        let matched = self.matching_interconnectors(|connector| connector.name == name);
+        matched.iter().filter(|&&idx| self.interconnectors[idx].edit_group_constraint(&mut edit)).count()
+    }
+
+    pub fn edit_group_interconnectors_by_tag<C, F>(&mut self, tag: &str, mut edit: F) -> usize
+        where C: GroupStepResolve<T> + Clone, F: FnMut(&mut C) -> bool
+    {
+        let matched = self.matching_interconnectors(|connector| connector.has_tag(tag));
+        matched.iter().filter(|&&idx| self.interconnectors[idx].edit_group_constraint(&mut edit)).count()
+    }
+
+    pub fn edit_selfconnector<C, F>(&mut self, handle: SelfHandle, edit: F) -> bool
+        where C: SelfStepResolve<T> + Clone, F: FnOnce(&mut C) -> bool
+    {
+        match self.selfconnector_index(handle) {
+            Some(idx) => self.selfconnectors[idx].edit_constraint(edit),
+            None => false,
+        }
+    }
+
+    pub fn edit_selfconnectors_by_name<C, F>(&mut self, name: &str, mut edit: F) -> usize
+        where C: SelfStepResolve<T> + Clone, F: FnMut(&mut C) -> bool
+    {
+        self.selfconnectors.iter_mut()
+            .filter(|connector| connector.name == name)
+            .map(|connector| connector.edit_constraint(&mut edit))
+            .filter(|&edited| edited)
+            .count()
+    }
+
+    fn matching_interconnectors<F: Fn(&InterConnector<T>) -> bool>(&self, predicate: F) -> Vec<usize> {
+        self.interconnectors.iter().enumerate()
+            .filter(|(_idx, connector)| predicate(connector))
+            .map(|(idx, _connector)| idx)
+            .collect()
+    }
+
+    pub(crate) fn remove_interconnector_at(&mut self, idx: usize) -> InterConnector<T> {
+        let handle = self.interconnector_slots.remove_at(idx);
+        let connector = self.interconnectors.swap_remove(idx);
+        self.colouring = None;
+        info!(self.logger, "interconnector removed"; "slot" => handle.slot, "generation" => handle.generation, "name" => &connector.name);
+        connector
+    }
+
+    fn remove_selfconnector_at(&mut self, idx: usize) -> SelfConnector<T> {
+        let handle = self.selfconnector_slots.remove_at(idx);
+        let connector = self.selfconnectors.swap_remove(idx);
+        info!(self.logger, "selfconnector removed"; "slot" => handle.slot, "generation" => handle.generation, "name" => &connector.name);
+        connector
+    }
+
+    // Jacobi needs its pass installed first, by Mesh::enable_jacobi
+    pub fn set_solver(&mut self, solver: Solver) -> Result<(), NacError> {
+        if solver == Solver::Jacobi && self.jacobi.is_none() {
+            return Err(NacError::InvalidArgument("the Jacobi solver needs Mesh::enable_jacobi".to_string()));
+        }
+        self.solver = solver;
+        Ok(())
+    }
+
+    // greedy graph colouring: no two interconnectors in a group share a node,
+    // so every group can be resolved concurrently without lock contention
+    pub fn color_interconnectors(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = vec!();
+        let mut group_nodes: Vec<HashSet<usize>> = vec!();
+        for (idx, connector) in self.interconnectors.iter().enumerate() {
+            let keys = connector.node_keys();
+            let free_group = group_nodes.iter().position(|used|
+                keys.iter().all(|key| !used.contains(key)));
+            let group = match free_group {
+                Some(group) => group,
+                None => {
+                    groups.push(vec!());
+                    group_nodes.push(HashSet::new());
+                    groups.len() - 1
+                }
+            };
+            groups[group].push(idx);
+            group_nodes[group].extend(keys);
+        }
+        groups
+    }
+
+    // the colouring from the last parallel step, unless connectors came or went since
+    fn colour_groups(&mut self) -> Arc<Vec<Vec<usize>>> {
+        self.sync_handles();
+        match &self.colouring {
+            Some(groups) => Arc::clone(groups),
+            None => {
+                let groups = Arc::new(self.color_interconnectors());
+                self.colouring = Some(Arc::clone(&groups));
+                groups
+            },
+        }
+    }
+
+    pub fn update(&mut self, delta: f64, physics_accuracy: u8) -> Result<(), NacError> {
+        match self.large_correction {
+            Some((_threshold, measure)) => self.step(delta, physics_accuracy, None, &measure)?,
+            None => self.step(delta, physics_accuracy, None, &|_node: &Node<T>, _updated: &Node<T>| 0.0)?,
+        };
+        Ok(())
+    }
+
+    // a fixed physics_accuracy runs every pass; a convergence stops once the constraint
+    // errors left after a pass fall under its tolerance
+    // Nodes poisoned before the step are dealt with by the poison policy first. One poisoned
+    // during the step stops it where it was found, leaving the passes before it applied, and
+    // is reported with the connectors that touch it; the panic that poisoned it unwinds out
+    // of the step as before. Interconnectors that broke are torn out once the passes are done.
+    fn step<M>(&mut self, delta: f64, max_iterations: u8, convergence: Option<Convergence>, measure: &M) -> Result<SolveReport, NacError>
+        where M: Fn(&Node<T>, &Node<T>) -> f64 + Sync
+    {
+        debug!(self.logger, "step start"; "dt" => delta, "elapsed" => self.elapsed, "nodes" => self.nodes.len(),
+            "interconnectors" => self.interconnectors.len(), "selfconnectors" => self.selfconnectors.len());
+        self.check_poison()?;
+        if self.history.is_some() {
+            let mut nodes = self.reachable_nodes();
+            nodes.retain(|node| !self.quarantine.contains_key(&(Arc::as_ptr(node) as usize)));
+            if let Some(history) = &mut self.history {
+                history.record(&nodes, self.elapsed)?;
+            }
+        }
+        let mut context = StepContext { dt: delta, elapsed: self.elapsed, damping: self.damping, ..StepContext::default() };
+        let integrated = match &self.integration {
+            Some(integration) => {
+                context.integrated = true;
+                integration(self, &context)?
+            },
+            None => vec!(),
+        };
+        let norm = convergence.map_or(ResidualNorm::Max, |convergence| convergence.norm);
+        let jacobi = match self.solver {
+            Solver::Jacobi => self.jacobi,
+            _ => None,
+        };
+        let report = match self.solver {
+            Solver::Parallel { threads } if threads > 1 => {
+                let groups = self.colour_groups();
+                self.resolve_parallel(&groups, threads, &context, max_iterations, convergence, measure)?
+            },
+            _ => {
+                let mut report = SolveReport::default();
+                for i in 0..max_iterations {
+                    let context = StepContext { iteration: i, ..context };
+                    let mut residuals = Residuals::default();
+                    match jacobi {
+                        Some(jacobi_pass) => {
+                            residuals = jacobi_pass(self, &context, measure)?;
+                            // corrections are averaged over the pass, so only the pass can be named
+                            self.log_correction(&residuals, &context, None);
+                        },
+                        None => for idx in 0..self.interconnectors.len() {
+                            self.resolve_interconnector(idx, &context, measure, &mut residuals)?;
+                        },
+                    }
+                    if convergence.is_some() {
+                        residuals = self.pass_errors(&context, measure)?;
+                    }
+                    report = SolveReport { iterations: i + 1, residual: residuals.norm(norm) };
+                    if converged(convergence, &report) {
+                        break;
+                    }
+                }
+                report
+            },
+        };
+        for (_idx, connector) in self.selfconnectors.iter().enumerate()
+            .filter(|(idx, _connector)| !integrated.get(*idx).cloned().unwrap_or(false))
+            .filter(|(_idx, connector)| !self.holds_selfconnector(connector)) {
+            connector.resolve(&context).map_err(|err| self.blame(err, &[&connector.node]))?;
+        }
+        self.tear()?;
+        self.elapsed += delta;
+        debug!(self.logger, "step end"; "iterations" => report.iterations, "residual" => report.residual, "elapsed" => self.elapsed);
+        Ok(report)
+    }
+
+    pub fn set_fixed_step(&mut self, delta: f64, max_substeps: u32, physics_accuracy: u8) {
+        self.fixed_step = FixedStep::new(delta, max_substeps, physics_accuracy);
+    }
+
+    // advance by wall-clock or frame time in fixed substeps,
+    // returning the interpolation alpha of the time left over;
+    // a frame time that is not finite is refused and leaves the accumulator alone
+    pub fn advance(&mut self, frame_time: f64) -> Result<f64, NacError> {
+        if !frame_time.is_finite() {
+            return Err(NacError::InvalidArgument(format!("frame time {} is not finite", frame_time)));
+        }
+        let substeps = self.fixed_step.substeps(frame_time);
+        let FixedStep { delta, physics_accuracy, .. } = self.fixed_step;
+        for _i in 0..substeps {
+            self.update(delta, physics_accuracy)?;
+        }
+        Ok(self.fixed_step.alpha())
+    }
+
+    // Runs every pass of a step over the colour groups in order. The workers are spawned once
+    // for the whole step and meet at a barrier after each group; a group too small to be worth
+    // splitting is left to the first worker. The first error or panic stops the step at the end
+    // of the group it happened in and is passed on from the calling thread.
+    fn resolve_parallel<M>(&self, groups: &[Vec<usize>], threads: usize, context: &StepContext, max_iterations: u8,
+        convergence: Option<Convergence>, measure: &M) -> Result<SolveReport, NacError>
+        where M: Fn(&Node<T>, &Node<T>) -> f64 + Sync
+    {
+        let norm = convergence.map_or(ResidualNorm::Max, |convergence| convergence.norm);
+        let barrier = Barrier::new(threads);
+        let pass_residuals = Mutex::new(Residuals::default());
+        let report = Mutex::new(SolveReport::default());
+        let failure: Mutex<Option<WorkerFailure>> = Mutex::new(None);
+        let done = AtomicBool::new(false);
+        // once anything failed the rest of the step is skipped, though workers still meet at the barriers
+        let attempt = |resolve: &mut dyn FnMut() -> Result<(), NacError>| {
+            if locked(&failure).is_some() {
+                return;
+            }
+            let failed = match panic::catch_unwind(panic::AssertUnwindSafe(resolve)) {
+                Ok(Ok(())) => return,
+                Ok(Err(err)) => WorkerFailure::Error(err),
+                Err(payload) => WorkerFailure::Panic(payload),
+            };
+            locked(&failure).get_or_insert(failed);
+        };
+
+        let work = |worker: usize| {
+            for i in 0..max_iterations {
+                let context = StepContext { iteration: i, ..*context };
+                let mut residuals = Residuals::default();
+                for group in groups {
+                    let chunk: &[usize] = if group.len() < PARALLEL_MIN_GROUP {
+                        if worker == 0 { group } else { &[] }
+                    } else {
+                        group.chunks(group.len().div_ceil(threads)).nth(worker).unwrap_or(&[])
+                    };
+                    attempt(&mut || chunk.iter().try_for_each(|&idx| self.resolve_interconnector(idx, &context, measure, &mut residuals)));
+                    barrier.wait();
+                }
+                locked(&pass_residuals).merge(residuals);
+                if barrier.wait().is_leader() {
+                    let mut residuals = std::mem::take(&mut *locked(&pass_residuals));
+                    if convergence.is_some() {
+                        attempt(&mut || self.pass_errors(&context, measure).map(|errors| residuals = errors));
+                    }
+                    let pass = SolveReport { iterations: i + 1, residual: residuals.norm(norm) };
+                    *locked(&report) = pass;
+                    if locked(&failure).is_some() || converged(convergence, &pass) {
+                        done.store(true, Ordering::SeqCst);
+                    }
+                }
+                barrier.wait();
+                if done.load(Ordering::SeqCst) {
+                    break;
+                }
+            }
+        };
+        thread::scope(|scope| {
+            let work = &work;
+            for worker in 1..threads {
+                scope.spawn(move || work(worker));
+            }
+            work(0);
         });
-        self.selfconnectors.iter().for_each(|connector|
-            connector.resolve());
+
+        match failure.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner()) {
+            // a worker's panic carries on in this thread, as it would have running serially
+            Some(WorkerFailure::Panic(payload)) => panic::resume_unwind(payload),
+            Some(WorkerFailure::Error(err)) => Err(err),
+            None => Ok(*locked(&report)),
+        }
+    }
+
+    // the constraint errors a pass left, which convergence is judged on
+    fn pass_errors<M>(&self, context: &StepContext, measure: &M) -> Result<Residuals, NacError>
+        where M: Fn(&Node<T>, &Node<T>) -> f64 + Sync
+    {
+        let mut residuals = Residuals::default();
+        for connector in self.interconnectors.iter().filter(|connector| !self.holds(connector)) {
+            connector.measure_error(context, measure, &mut residuals).map_err(|err| self.blame(err, &connector.members()))?;
+        }
+        Ok(residuals)
+    }
+
+    // one interconnector's turn in a pass, sat out while it touches a quarantined node
+    fn resolve_interconnector<M>(&self, idx: usize, context: &StepContext, measure: &M, residuals: &mut Residuals) -> Result<(), NacError>
+        where M: Fn(&Node<T>, &Node<T>) -> f64 + Sync
+    {
+        let connector = &self.interconnectors[idx];
+        if self.holds(connector) {
+            return Ok(());
+        }
+        let mut connector_residuals = Residuals::default();
+        connector.resolve_measured(context, measure, &mut connector_residuals).map_err(|err| self.blame(err, &connector.members()))?;
+        self.log_correction(&connector_residuals, context, Some(idx));
+        residuals.merge(connector_residuals);
+        Ok(())
+    }
+
+    fn log_correction(&self, residuals: &Residuals, context: &StepContext, interconnector: Option<usize>) {
+        if let Some((threshold, _measure)) = self.large_correction {
+            if residuals.max > threshold {
+                match interconnector {
+                    Some(idx) => warn!(self.logger, "large correction"; "correction" => residuals.max, "threshold" => threshold,
+                        "iteration" => context.iteration, "slot" => self.interconnector_handle(idx).unwrap_or_default().slot,
+                        "name" => &self.interconnectors[idx].name),
+                    None => warn!(self.logger, "large correction"; "correction" => residuals.max, "threshold" => threshold,
+                        "iteration" => context.iteration),
+                }
+            }
+        }
     }
 
 }
 
+impl<T: Sync + Send + Residual + 'static> Mesh<T> {
+    // keep iterating until the constraints settle instead of a fixed physics_accuracy
+    pub fn update_converged(&mut self, delta: f64, convergence: Convergence) -> Result<SolveReport, NacError> {
+        self.step(delta, convergence.max_iterations, Some(convergence),
+            &|node: &Node<T>, updated: &Node<T>| node.data.residual(&updated.data))
+    }
+}
+
+impl<T: Sync + Send + JacobiCorrection + 'static> Mesh<T> {
+    pub fn enable_jacobi(&mut self) {
+        self.jacobi = Some(Self::jacobi_pass);
+        self.solver = Solver::Jacobi;
+    }
+
+    fn jacobi_pass(&self, context: &StepContext, measure: &(dyn Fn(&Node<T>, &Node<T>) -> f64 + Sync)) -> Result<Residuals, NacError> {
+        let mut residuals = Residuals::default();
+        let mut totals: HashMap<usize, (SharedNode<T>, T::Correction, usize)> = HashMap::new();
+        let mut accumulate = |shared: &SharedNode<T>, correction: T::Correction| {
+            let entry = totals.entry(Arc::as_ptr(shared) as usize)
+                .or_insert_with(|| (Arc::clone(shared), T::Correction::default(), 0));
+            entry.1 = T::accumulate(entry.1, correction);
+            entry.2 += 1;
+        };
+
+        for connector in self.interconnectors.iter().filter(|connector| connector.enabled && !self.holds(connector)) {
+            let relation = match &connector.relation {
+                InterRelation::Pairwise(relation) => relation,
+                InterRelation::Group(relation) => {
+                    let members = connector.members();
+                    let guards = members.iter().map(|member| self.read_blamed(member)).collect::<Result<Vec<_>, _>>()?;
+                    let nodes: Vec<&Node<T>> = guards.iter().map(|guard| &**guard).collect();
+                    let updated = relation.resolve(&nodes, context);
+                    members.iter().zip(nodes.iter()).zip(updated.iter()).for_each(|((member, node), updated_node)| {
+                        residuals.record(measure(node, updated_node));
+                        accumulate(member, node.data.correction(&updated_node.data));
+                    });
+                    continue;
+                },
+            };
+            if let Some(constraints) = &connector.constraints {
+                for constraint in constraints {
+                    let (node, constraint_node) = (self.read_blamed(&connector.node)?, self.read_blamed(constraint)?);
+                    let (updated_node, updated_constraint) = relation.resolve(&node, &constraint_node, context);
+                    residuals.record(measure(&node, &updated_node));
+                    residuals.record(measure(&constraint_node, &updated_constraint));
+                    accumulate(&connector.node, node.data.correction(&updated_node.data));
+                    accumulate(constraint, constraint_node.data.correction(&updated_constraint.data));
+                }
+            }
+        }
+
+        for (shared, total, count) in totals.values() {
+            let mut node = self.write_blamed(shared)?;
+            node.data = node.data.corrected(*total, *count);
+        }
+        Ok(residuals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verlet::Verlet;
+    use crate::constraint::Constraint;
+    use crate::damping::Damping;
+
+    fn pair(spring: f64) -> Mesh<Verlet> {
+        let nodes: Vec<SharedNode<Verlet>> = vec!(
+            Arc::new(RwLock::new(Node::new(Verlet::new_pinned(0.0, 0.0)))),
+            Arc::new(RwLock::new(Node::new(Verlet::new(2.0, 0.0)))));
+        let link = InterConnector::stepped(Arc::clone(&nodes[1]), Some(vec!(Arc::clone(&nodes[0]))),
+            Arc::new(Constraint::Distance { spacing: 1.0, spring, tear: None })).with_name("link");
+        Mesh::new(nodes, vec!(link), vec!())
+    }
+
+    // a sheet hanging from its pinned top row, its lower rows pushed sideways to start with
+    fn sheet(width: usize, height: usize) -> Mesh<Verlet> {
+        let nodes: Vec<SharedNode<Verlet>> = (0..width * height).map(|idx| {
+            let (column, row) = ((idx % width) as f64, (idx / width) as f64);
+            let node = if idx < width { Verlet::new_pinned(column, row) } else { Verlet::new(column + 0.3 * row, row) };
+            Arc::new(RwLock::new(Node::new(node)))
+        }).collect();
+        let distance: Arc<dyn InterStepResolve<Verlet>> = Arc::new(Constraint::Distance { spacing: 1.0, spring: 0.5, tear: None });
+        let link = |a: usize, b: usize| InterConnector::stepped(Arc::clone(&nodes[a]), Some(vec!(Arc::clone(&nodes[b]))), Arc::clone(&distance));
+        let mut interconnectors = vec!();
+        for idx in 0..width * height {
+            if idx % width > 0 {
+                interconnectors.push(link(idx, idx - 1));
+            }
+            if idx >= width {
+                interconnectors.push(link(idx, idx - width));
+            }
+        }
+        let gravity: Arc<dyn SelfStepResolve<Verlet>> = Arc::new(Constraint::Gravity { gravity: 10.0, delta: None });
+        let selfconnectors = nodes[width..].iter()
+            .map(|node| SelfConnector::stepped("gravity", Arc::clone(node), Arc::clone(&gravity)))
+            .collect();
+        let mut mesh = Mesh::new(nodes, interconnectors, selfconnectors);
+        mesh.set_damping(Damping::new(2.0, 0.0));
+        mesh
+    }
+
+    fn positions(mesh: &Mesh<Verlet>) -> Vec<(f64, f64)> {
+        mesh.nodes.iter().map(|node| {
+            let position = node.read().unwrap().data.position;
+            (position.x, position.y)
+        }).collect()
+    }
+
+    #[test]
+    fn colour_groups_never_share_a_node() {
+        let mut mesh = sheet(64, 4);
+        let groups = mesh.colour_groups();
+        assert!(groups.iter().any(|group| group.len() >= PARALLEL_MIN_GROUP));
+        assert_eq!(groups.iter().map(Vec::len).sum::<usize>(), mesh.interconnectors.len());
+        for group in groups.iter() {
+            let mut seen = HashSet::new();
+            for &idx in group {
+                assert!(mesh.interconnectors[idx].node_keys().into_iter().all(|key| seen.insert(key)));
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_solver_settles_where_the_serial_one_does() {
+        let (mut serial, mut parallel) = (sheet(64, 4), sheet(64, 4));
+        parallel.set_solver(Solver::Parallel { threads: 4 }).unwrap();
+        // the colour groups resolve in another order, so only the resting shape is the same
+        for _ in 0..200 {
+            serial.update(0.016, 20).unwrap();
+            parallel.update(0.016, 20).unwrap();
+        }
+        let offset = positions(&serial).iter().zip(positions(&parallel).iter())
+            .map(|(a, b)| (a.0 - b.0).hypot(a.1 - b.1))
+            .fold(0.0, f64::max);
+        assert!(offset < 0.01);
+    }
+
+    #[test]
+    fn jacobi_results_do_not_depend_on_connector_order() {
+        let mut forward = sheet(8, 4);
+        let Mesh { nodes, mut interconnectors, selfconnectors, .. } = sheet(8, 4);
+        interconnectors.reverse();
+        let mut reversed = Mesh::new(nodes, interconnectors, selfconnectors);
+        reversed.set_damping(Damping::new(2.0, 0.0));
+        assert!(matches!(reversed.set_solver(Solver::Jacobi), Err(NacError::InvalidArgument(_))));
+        forward.enable_jacobi();
+        reversed.enable_jacobi();
+        for _ in 0..50 {
+            forward.update(0.016, 4).unwrap();
+            reversed.update(0.016, 4).unwrap();
+        }
+        let offset = positions(&forward).iter().zip(positions(&reversed).iter())
+            .map(|(a, b)| (a.0 - b.0).hypot(a.1 - b.1))
+            .fold(0.0, f64::max);
+        assert!(offset < 1e-9);
+    }
+
+    // four free links in a row, named and tagged by which end of the row they are on
+    fn tagged_links() -> Mesh<Verlet> {
+        let nodes: Vec<SharedNode<Verlet>> = (0..5).map(|i| Arc::new(RwLock::new(Node::new(Verlet::new(2.0 * i as f64, 0.0))))).collect();
+        let distance: Arc<dyn InterStepResolve<Verlet>> = Arc::new(Constraint::Distance { spacing: 1.0, spring: 0.5, tear: None });
+        let interconnectors = (0..4).map(|i| {
+            let link = InterConnector::stepped(Arc::clone(&nodes[i]), Some(vec!(Arc::clone(&nodes[i + 1]))), Arc::clone(&distance))
+                .with_name(if i % 2 == 0 { "even" } else { "odd" });
+            if i < 2 { link.with_tag("left") } else { link.with_tag("right") }
+        }).collect();
+        Mesh::new(nodes, interconnectors, vec!())
+    }
+
+    fn names(mesh: &Mesh<Verlet>, handles: &[InterHandle]) -> Vec<String> {
+        handles.iter().map(|&handle| mesh.interconnector(handle).unwrap().name.clone()).collect()
+    }
+
+    #[test]
+    fn interconnectors_are_found_by_name_and_tag() {
+        let mut mesh = tagged_links();
+        let even = mesh.interconnectors_by_name("even");
+        assert_eq!(even, vec!(mesh.interconnector_handle(0).unwrap(), mesh.interconnector_handle(2).unwrap()));
+        let right = mesh.interconnectors_by_tag("right");
+        assert_eq!(names(&mesh, &right), vec!("even", "odd"));
+        assert!(mesh.interconnectors_by_name("missing").is_empty());
+
+        // handles found before a removal still reach the same connectors after it
+        mesh.remove_interconnector(mesh.interconnector_handle(0).unwrap()).unwrap();
+        assert!(mesh.interconnector(even[0]).is_none());
+        assert!(mesh.interconnector(even[1]).unwrap().has_tag("right"));
+        assert_eq!(mesh.interconnectors_by_name("even"), vec!(even[1]));
+    }
+
+    #[test]
+    fn disabled_interconnectors_do_not_resolve() {
+        let mut mesh = pair(0.5);
+        assert_eq!(mesh.set_interconnectors_enabled_by_name("link", false), 1);
+        mesh.update(0.016, 4).unwrap();
+        assert_eq!(mesh.nodes[1].read().unwrap().data.position.x, 2.0);
+
+        mesh.set_interconnectors_enabled_by_name("link", true);
+        mesh.update(0.016, 4).unwrap();
+        assert!(mesh.nodes[1].read().unwrap().data.position.x < 2.0);
+    }
+
+    #[test]
+    fn removing_by_tag_leaves_the_other_connectors() {
+        let mut mesh = tagged_links();
+        let left = mesh.interconnectors_by_tag("left");
+        let right = mesh.interconnectors_by_tag("right");
+        assert_eq!(mesh.remove_interconnectors_by_tag("left"), 2);
+        assert_eq!(mesh.interconnectors.len(), 2);
+        assert!(left.iter().all(|&handle| mesh.interconnector(handle).is_none()));
+        assert_eq!(names(&mesh, &right), vec!("even", "odd"));
+        assert!(mesh.interconnectors_by_tag("left").is_empty());
+        assert_eq!(mesh.remove_interconnectors_by_tag("left"), 0);
+    }
+
+    #[test]
+    fn convergence_is_judged_on_constraint_error() {
+        let convergence = Convergence { tolerance: 0.01, max_iterations: 200, norm: ResidualNorm::Max };
+        // a weak spring corrects little per pass while the link is still far from its spacing
+        let mut mesh = pair(0.05);
+        let report = mesh.update_converged(0.016, convergence).unwrap();
+        let x = mesh.nodes[1].read().unwrap().data.position.x;
+        assert!(report.iterations > 1);
+        assert!(report.residual <= convergence.tolerance);
+        assert!(((x - 1.0).abs() - report.residual).abs() < 1e-9);
+    }
+
+    #[test]
+    fn advance_refuses_frame_times_that_are_not_finite() {
+        let mut mesh: Mesh<f64> = Mesh::new(vec!(), vec!(), vec!());
+        mesh.set_fixed_step(0.01, 4, 1);
+        mesh.advance(0.015).unwrap();
+        let accumulator = mesh.fixed_step.accumulator();
+
+        for frame_time in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            assert!(matches!(mesh.advance(frame_time), Err(NacError::InvalidArgument(_))));
+            assert_eq!(mesh.fixed_step.accumulator(), accumulator);
+        }
+        let alpha = mesh.advance(0.0).unwrap();
+        assert!((alpha - 0.5).abs() < 1e-9);
+        assert!((mesh.elapsed - 0.01).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::nac::{Node, SharedNode, InterConnector, SelfConnector, Mesh, read_node, write_node};
+use crate::handle::{NodeHandle, InterHandle, SelfHandle};
+use crate::error::NacError;
+
+use std::sync::{Arc, RwLockReadGuard, RwLockWriteGuard};
+use std::collections::HashSet;
+use std::fmt;
+
+// What a step does about nodes whose lock was poisoned by a panic while it was held.
+// The check runs once at the start of every step, before anything is read.
+#[derive(Debug,Default,Clone,Copy,PartialEq)]
+pub enum PoisonPolicy {
+    // stop with NacError::PoisonedNode until the node is released
+    #[default]
+    Fail,
+    // clear the poison and step the node as the panic left it
+    Recover,
+    // hold the node where it is: it is not integrated, and connectors touching it are
+    // skipped, until it is released
+    Quarantine,
+}
+
+// A poisoned node and the connectors that read or write it, one of which was most likely
+// resolving when the panic happened.
+#[derive(Debug,Default,Clone,PartialEq)]
+pub struct Poisoning {
+    // None for nodes only reached through a connector, or found outside any mesh
+    pub node: Option<NodeHandle>,
+    pub interconnectors: Vec<InterHandle>,
+    pub selfconnectors: Vec<SelfHandle>,
+}
+
+impl fmt::Display for Poisoning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.node {
+            Some(node) => write!(f, "node in slot {}", node.slot)?,
+            None => write!(f, "node")?,
+        }
+        let inter: Vec<u32> = self.interconnectors.iter().map(|handle| handle.slot).collect();
+        let selfs: Vec<u32> = self.selfconnectors.iter().map(|handle| handle.slot).collect();
+        match (inter.is_empty(), selfs.is_empty()) {
+            (true, true) => Ok(()),
+            (false, true) => write!(f, " (interconnector slots {:?})", inter),
+            (true, false) => write!(f, " (selfconnector slots {:?})", selfs),
+            (false, false) => write!(f, " (interconnector slots {:?}, selfconnector slots {:?})", inter, selfs),
+        }
+    }
+}
+
+fn node_key<T: Sync + Send + 'static>(node: &SharedNode<T>) -> usize {
+    Arc::as_ptr(node) as usize
+}
+
+impl<T: Sync + Send + 'static> Mesh<T> {
+    pub fn set_poison_policy(&mut self, policy: PoisonPolicy) {
+        self.poison_policy = policy;
+    }
+
+    pub fn poison_policy(&self) -> PoisonPolicy {
+        self.poison_policy
+    }
+
+    // Applies the poison policy to every node the mesh steps, the mesh nodes and any node
+    // a connector reaches. Steps do this first; call it directly to deal with a panic
+    // before the next one.
+    pub fn check_poison(&mut self) -> Result<(), NacError> {
+        self.sync_handles();
+        for node in self.poisoned_nodes() {
+            if self.quarantine.contains_key(&node_key(&node)) {
+                continue;
+            }
+            let poisoning = self.poisoning(&node);
+            warn!(self.logger, "node poisoned"; "poisoned" => %poisoning, "policy" => ?self.poison_policy);
+            match self.poison_policy {
+                PoisonPolicy::Fail => return Err(NacError::PoisonedNode(poisoning)),
+                PoisonPolicy::Recover => node.clear_poison(),
+                PoisonPolicy::Quarantine => { self.quarantine.insert(node_key(&node), node); },
+            }
+            self.poisonings.push(poisoning);
+        }
+        Ok(())
+    }
+
+    // the nodes recovered or quarantined since the poisonings were last taken, oldest first
+    pub fn poisonings(&self) -> &[Poisoning] {
+        &self.poisonings
+    }
+
+    pub fn take_poisonings(&mut self) -> Vec<Poisoning> {
+        std::mem::take(&mut self.poisonings)
+    }
+
+    pub fn is_quarantined(&self, node: &SharedNode<T>) -> bool {
+        !self.quarantine.is_empty() && self.quarantine.contains_key(&node_key(node))
+    }
+
+    pub fn quarantined_nodes(&self) -> Vec<NodeHandle> {
+        self.nodes.iter().enumerate()
+            .filter(|(_idx, node)| self.is_quarantined(node))
+            .filter_map(|(idx, _node)| self.node_handle(idx))
+            .collect()
+    }
+
+    // Clears the node's poison and lets it step again, as the panic left it.
+    // Returns false if the node was not quarantined.
+    pub fn release_node(&mut self, handle: NodeHandle) -> bool {
+        let key = match self.node(handle) {
+            Some(node) => node_key(node),
+            None => return false,
+        };
+        match self.quarantine.remove(&key) {
+            Some(node) => {
+                node.clear_poison();
+                true
+            },
+            None => false,
+        }
+    }
+
+    // releases every quarantined node, including those only reached through connectors
+    pub fn release_quarantine(&mut self) -> usize {
+        let released = self.quarantine.len();
+        self.quarantine.drain().for_each(|(_key, node)| node.clear_poison());
+        released
+    }
+
+    // whether a connector touches a quarantined node, and sits the step out
+    pub(crate) fn holds(&self, connector: &InterConnector<T>) -> bool {
+        !self.quarantine.is_empty() && connector.members().into_iter().any(|member| self.is_quarantined(member))
+    }
+
+    pub(crate) fn holds_selfconnector(&self, connector: &SelfConnector<T>) -> bool {
+        self.is_quarantined(&connector.node)
+    }
+
+    // the nodes a step would lock that are currently poisoned, each once
+    fn poisoned_nodes(&self) -> Vec<SharedNode<T>> {
+        let mut seen = HashSet::new();
+        self.nodes.iter()
+            .chain(self.interconnectors.iter().flat_map(InterConnector::members))
+            .chain(self.selfconnectors.iter().map(|connector| &connector.node))
+            .filter(|node| seen.insert(node_key(node)))
+            .filter(|node| node.is_poisoned())
+            .map(Arc::clone)
+            .collect()
+    }
+
+    fn poisoning(&self, node: &SharedNode<T>) -> Poisoning {
+        Poisoning {
+            node: self.nodes.iter().position(|candidate| Arc::ptr_eq(candidate, node))
+                .and_then(|idx| self.node_handle(idx)),
+            interconnectors: self.interconnectors.iter().enumerate()
+                .filter(|(_idx, connector)| connector.touches(node))
+                .filter_map(|(idx, _connector)| self.interconnector_handle(idx))
+                .collect(),
+            selfconnectors: self.selfconnectors.iter().enumerate()
+                .filter(|(_idx, connector)| Arc::ptr_eq(&connector.node, node))
+                .filter_map(|(idx, _connector)| self.selfconnector_handle(idx))
+                .collect(),
+        }
+    }
+
+    // Fills in where a lock poisoned during the step was found, from the nodes of the
+    // connector that ran into it. Other errors pass through.
+    pub(crate) fn blame(&self, err: NacError, members: &[&SharedNode<T>]) -> NacError {
+        match err {
+            NacError::PoisonedNode(poisoning) if poisoning == Poisoning::default() =>
+                match members.iter().find(|member| member.is_poisoned()) {
+                    Some(member) => NacError::PoisonedNode(self.poisoning(member)),
+                    None => NacError::PoisonedNode(poisoning),
+                },
+            err => err,
+        }
+    }
+
+    // node locks for the passes, blaming the node's connectors if it was poisoned
+    pub(crate) fn read_blamed<'a>(&self, node: &'a SharedNode<T>) -> Result<RwLockReadGuard<'a, Node<T>>, NacError> {
+        read_node(node).map_err(|err| self.blame(err, &[node]))
+    }
+
+    pub(crate) fn write_blamed<'a>(&self, node: &'a SharedNode<T>) -> Result<RwLockWriteGuard<'a, Node<T>>, NacError> {
+        write_node(node).map_err(|err| self.blame(err, &[node]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nac::SelfStepResolve;
+    use crate::constraint::Constraint;
+    use crate::verlet::Verlet;
+
+    use std::sync::RwLock;
+    use std::panic;
+
+    // two falling nodes, the first of them poisoned
+    fn poisoned(policy: PoisonPolicy) -> (Mesh<Verlet>, Vec<SharedNode<Verlet>>) {
+        let nodes: Vec<SharedNode<Verlet>> = (0..2).map(|i| Arc::new(RwLock::new(Node::new(Verlet::new(i as f64, 0.0))))).collect();
+        let gravity: Arc<dyn SelfStepResolve<Verlet>> = Arc::new(Constraint::Gravity { gravity: 10.0, delta: None });
+        let selfconnectors = nodes.iter().map(|node| SelfConnector::stepped("gravity", Arc::clone(node), Arc::clone(&gravity))).collect();
+        let mut mesh = Mesh::new(nodes.clone(), vec!(), selfconnectors);
+        mesh.set_poison_policy(policy);
+        let node = Arc::clone(&nodes[0]);
+        let _ = panic::catch_unwind(move || {
+            let _guard = node.write().unwrap();
+            panic!("relation failed");
+        });
+        assert!(nodes[0].is_poisoned());
+        (mesh, nodes)
+    }
+
+    fn y(node: &SharedNode<Verlet>) -> f64 {
+        node.read().unwrap_or_else(|poisoned| poisoned.into_inner()).data.position.y
+    }
+
+    #[test]
+    fn fail_reports_the_node_and_its_connectors() {
+        let (mut mesh, nodes) = poisoned(PoisonPolicy::Fail);
+        let expected = Poisoning {
+            node: mesh.node_handle(0),
+            interconnectors: vec!(),
+            selfconnectors: vec!(mesh.selfconnector_handle(0).unwrap()),
+        };
+        match mesh.update(0.016, 1) {
+            Err(NacError::PoisonedNode(poisoning)) => assert_eq!(poisoning, expected),
+            other => panic!("expected a poisoned node, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(y(&nodes[1]), 0.0);
+    }
+
+    #[test]
+    fn recover_clears_the_poison_and_steps_on() {
+        let (mut mesh, nodes) = poisoned(PoisonPolicy::Recover);
+        mesh.update(0.016, 1).unwrap();
+        assert!(!nodes[0].is_poisoned());
+        assert!(y(&nodes[0]) > 0.0);
+        assert_eq!(mesh.take_poisonings().len(), 1);
+    }
+
+    #[test]
+    fn quarantine_holds_the_node_until_it_is_released() {
+        let (mut mesh, nodes) = poisoned(PoisonPolicy::Quarantine);
+        mesh.update(0.016, 1).unwrap();
+        assert_eq!(y(&nodes[0]), 0.0);
+        assert!(y(&nodes[1]) > 0.0);
+        assert_eq!(mesh.quarantined_nodes(), vec!(mesh.node_handle(0).unwrap()));
+
+        assert!(mesh.release_node(mesh.node_handle(0).unwrap()));
+        mesh.update(0.016, 1).unwrap();
+        assert!(y(&nodes[0]) > 0.0);
+    }
+}
@@ -0,0 +1,433 @@
+use crate::nac::{Node, SharedNode, InterConnector, SelfConnector, InterRelation, GroupStepResolve, SelfStepResolve, Mesh};
+use crate::verlet::{VerletNode, Point, SharedRng, seeded_rng};
+use crate::constraint::{Constraint, Tear};
+use crate::damping::Damping;
+
+use serde::{Deserialize, Serialize};
+
+use std::sync::{Arc, RwLock};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Declarative description of a Verlet mesh, read from JSON:
+//
+// {
+//   "seed": 7,
+//   "damping": 0.5,
+//   "nodes": [{ "x": 0.0, "y": 0.0, "pinned": true }, { "x": 5.0, "y": 0.0, "z": 1.0, "mass": 2.0 }],
+//   "interconnectors": [{ "node": 1, "constraints": [0], "spacing": 5.0, "spring": 0.5, "name": "cloth", "tags": ["top"],
+//                         "tear": { "type": "ratio", "ratio": 1.5 } },
+//                       { "node": 0, "constraints": [1, 2], "constraint": { "type": "angle", "angle": 1.57, "stiffness": 0.5 } }],
+//   "selfconnectors": [
+//     { "node": 1, "name": "gravity", "constraint": { "type": "gravity", "gravity": 120 } },
+//     { "node": 1, "name": "wind", "constraint": { "type": "wind", "horz_strength": 3.0, "vert_strength": 0.0 } }
+//   ]
+// }
+//
+// Nodes are referred to by their position in "nodes". Constraints that take a delta
+// follow the timestep the mesh is updated with unless one is given. Wind strengths are how
+// far a gust moves a node in one step, or accelerations with "accelerated": true. Wind
+// without a seed draws from a generator seeded with the scene seed. Interconnectors without a "tear" never break.
+// An interconnector with a "constraint" relates its node and constraints as one group (see
+// `InterConnector::group`) instead of keeping them at "spacing", and ignores "spring" and "tear".
+// Nodes without a "mass" weigh 1, and have no damping of their own without a "damping".
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub struct Scene {
+    // seeds the generator shared by wind constraints that do not give their own
+    #[serde(default)]
+    pub seed: u64,
+    // the mesh's linear damping and air drag, see damping::Damping
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub damping: f64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub drag: f64,
+    pub nodes: Vec<NodeSpec>,
+    #[serde(default)]
+    pub interconnectors: Vec<InterConnectorSpec>,
+    #[serde(default)]
+    pub selfconnectors: Vec<SelfConnectorSpec>,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub struct NodeSpec {
+    pub x: f64,
+    pub y: f64,
+    // dropped by two dimensional meshes
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub z: f64,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default = "unit_mass", skip_serializing_if = "is_unit_mass")]
+    pub mass: f64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub damping: f64,
+}
+
+fn unit_mass() -> f64 {
+    1.0
+}
+
+fn is_unit_mass(mass: &f64) -> bool {
+    *mass == 1.0
+}
+
+fn is_zero(value: &f64) -> bool {
+    *value == 0.0
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub struct InterConnectorSpec {
+    pub node: usize,
+    pub constraints: Vec<usize>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub spacing: f64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub spring: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tear: Option<TearSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<GroupConstraintSpec>,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug,Clone,Copy,Serialize,Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GroupConstraintSpec {
+    Angle { angle: f64, stiffness: f64 },
+    Area { area: f64, stiffness: f64 },
+    Centroid { x: f64, y: f64, stiffness: f64 },
+}
+
+impl From<GroupConstraintSpec> for Constraint {
+    fn from(spec: GroupConstraintSpec) -> Self {
+        match spec {
+            GroupConstraintSpec::Angle { angle, stiffness } => Constraint::Angle { angle, stiffness },
+            GroupConstraintSpec::Area { area, stiffness } => Constraint::Area { area, stiffness },
+            GroupConstraintSpec::Centroid { x, y, stiffness } => Constraint::Centroid { x, y, stiffness },
+        }
+    }
+}
+
+#[derive(Debug,Clone,Copy,Serialize,Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TearSpec {
+    Ratio { ratio: f64 },
+    Distance { distance: f64 },
+}
+
+impl From<TearSpec> for Tear {
+    fn from(spec: TearSpec) -> Self {
+        match spec {
+            TearSpec::Ratio { ratio } => Tear::Ratio(ratio),
+            TearSpec::Distance { distance } => Tear::Distance(distance),
+        }
+    }
+}
+
+impl From<Tear> for TearSpec {
+    fn from(tear: Tear) -> Self {
+        match tear {
+            Tear::Ratio(ratio) => TearSpec::Ratio { ratio },
+            Tear::Distance(distance) => TearSpec::Distance { distance },
+        }
+    }
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub struct SelfConnectorSpec {
+    pub node: usize,
+    pub name: String,
+    pub constraint: SelfConstraintSpec,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SelfConstraintSpec {
+    Gravity { gravity: f64, delta: Option<f64> },
+    BoundGravity { gravity: f64, boundary: f64, delta: Option<f64> },
+    GroundBoundary { boundary: f64 },
+    Impetus { x_force: f64, y_force: f64, delta: Option<f64> },
+    Wind {
+        horz_strength: f64,
+        vert_strength: f64,
+        #[serde(default, skip_serializing_if = "is_false")]
+        accelerated: bool,
+        seed: Option<u64>,
+    },
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    // a connector referred to a node index past the end of "nodes"
+    NodeIndex(usize),
+    // a connector is a closure, or a constraint on the wrong kind of connector, and has no scene form
+    Unrepresentable(String),
+    // node at this index of the mesh was poisoned by a panic and could not be read
+    PoisonedNode(usize),
+    // node at this index of "nodes" has a mass that is not positive
+    Mass(usize),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "could not read scene: {}", err),
+            SceneError::Parse(err) => write!(f, "could not parse scene: {}", err),
+            SceneError::NodeIndex(idx) => write!(f, "scene refers to missing node {}", idx),
+            SceneError::Unrepresentable(name) => write!(f, "connector \"{}\" cannot be written to a scene", name),
+            SceneError::PoisonedNode(idx) => write!(f, "mesh node {} is poisoned", idx),
+            SceneError::Mass(idx) => write!(f, "scene node {} needs a positive mass", idx),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(err: io::Error) -> Self {
+        SceneError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SceneError {
+    fn from(err: serde_json::Error) -> Self {
+        SceneError::Parse(err)
+    }
+}
+
+impl Scene {
+    pub fn from_json(source: &str) -> Result<Scene, SceneError> {
+        Ok(serde_json::from_str(source)?)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Scene, SceneError> {
+        Scene::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn to_mesh<V: VerletNode>(&self) -> Result<Mesh<V>, SceneError> {
+        if let Some(idx) = self.nodes.iter().position(|node| !(node.mass > 0.0 && node.mass.is_finite())) {
+            return Err(SceneError::Mass(idx));
+        }
+        let nodes: Vec<SharedNode<V>> = self.nodes.iter().map(|node| {
+            let position = V::Point::from_xyz(node.x, node.y, node.z);
+            if node.pinned {
+                Arc::new(RwLock::new(Node::new(V::new_pinned_at(position).with_mass(node.mass).with_damping(node.damping))))
+            } else {
+                Arc::new(RwLock::new(Node::new(V::new_at(position).with_mass(node.mass).with_damping(node.damping))))
+            }
+        }).collect();
+        let node = |idx: usize| nodes.get(idx).map(Arc::clone).ok_or(SceneError::NodeIndex(idx));
+
+        let mut interconnectors: Vec<InterConnector<V>> = vec!();
+        for spec in &self.interconnectors {
+            let constraints = spec.constraints.iter().map(|&idx| node(idx)).collect::<Result<Vec<_>, _>>()?;
+            let interconnector = match spec.constraint {
+                Some(group) => {
+                    let relation: Arc<dyn GroupStepResolve<V>> = Arc::new(Constraint::from(group));
+                    InterConnector::group(node(spec.node)?, constraints, relation)
+                },
+                None => {
+                    let constraint = Constraint::Distance { spacing: spec.spacing, spring: spec.spring, tear: spec.tear.map(Tear::from) };
+                    InterConnector::stepped(node(spec.node)?, Some(constraints), Arc::new(constraint))
+                },
+            };
+            let mut interconnector = interconnector.with_name(&spec.name);
+            interconnector.tags.extend(spec.tags.iter().cloned());
+            interconnectors.push(interconnector);
+        }
+
+        // wind constraints with the same seed share one generator
+        let mut rngs: HashMap<u64, SharedRng> = HashMap::new();
+        let mut selfconnectors: Vec<SelfConnector<V>> = vec!();
+        for spec in &self.selfconnectors {
+            let constraint = match spec.constraint {
+                SelfConstraintSpec::Gravity { gravity, delta } => Constraint::Gravity { gravity, delta },
+                SelfConstraintSpec::BoundGravity { gravity, boundary, delta } => Constraint::BoundGravity { gravity, boundary, delta },
+                SelfConstraintSpec::GroundBoundary { boundary } => Constraint::GroundBoundary { boundary },
+                SelfConstraintSpec::Impetus { x_force, y_force, delta } => Constraint::Force { horz_strength: x_force, vert_strength: y_force, delta },
+                SelfConstraintSpec::Wind { horz_strength, vert_strength, accelerated, seed } => {
+                    let seed = seed.unwrap_or(self.seed);
+                    let rng = Arc::clone(rngs.entry(seed).or_insert_with(|| seeded_rng(seed)));
+                    Constraint::Wind { horz_strength, vert_strength, accelerated, seed, rng }
+                },
+            };
+            let relation: Arc<dyn SelfStepResolve<V>> = Arc::new(constraint);
+            selfconnectors.push(SelfConnector::stepped(&spec.name, node(spec.node)?, relation));
+        }
+
+        let mut mesh = Mesh::new(nodes, interconnectors, selfconnectors);
+        mesh.set_damping(Damping::new(self.damping, self.drag));
+        Ok(mesh)
+    }
+}
+
+// The scene a mesh would be loaded from. Positions are written as they are now, so the
+// motion nodes carry is not kept, and wind generators restart from their seeds.
+impl Scene {
+    pub fn from_mesh<V: VerletNode>(mesh: &Mesh<V>) -> Result<Scene, SceneError> {
+        let indices: HashMap<*const RwLock<Node<V>>, usize> = mesh.nodes.iter().enumerate()
+            .map(|(idx, node)| (Arc::as_ptr(node), idx)).collect();
+        let index = |node: &SharedNode<V>, name: &str| indices.get(&Arc::as_ptr(node)).cloned()
+            .ok_or_else(|| SceneError::Unrepresentable(name.to_owned()));
+
+        let nodes = mesh.nodes.iter().enumerate().map(|(idx, node)| {
+            let node = node.read().map_err(|_| SceneError::PoisonedNode(idx))?;
+            let position = node.data.point();
+            Ok(NodeSpec { x: position.x(), y: position.y(), z: position.z(), pinned: node.data.is_pinned(), mass: node.data.mass(), damping: node.data.damping() })
+        }).collect::<Result<_, SceneError>>()?;
+
+        let mut interconnectors = vec!();
+        for connector in &mesh.interconnectors {
+            let grouped = matches!(connector.relation(), InterRelation::Group(_));
+            let (spacing, spring, tear, constraint) = match connector.constraint::<Constraint>() {
+                Some(Constraint::Distance { spacing, spring, tear }) => (*spacing, *spring, *tear, None),
+                Some(Constraint::Angle { angle, stiffness }) if grouped =>
+                    (0.0, 0.0, None, Some(GroupConstraintSpec::Angle { angle: *angle, stiffness: *stiffness })),
+                Some(Constraint::Area { area, stiffness }) if grouped =>
+                    (0.0, 0.0, None, Some(GroupConstraintSpec::Area { area: *area, stiffness: *stiffness })),
+                Some(Constraint::Centroid { x, y, stiffness }) if grouped =>
+                    (0.0, 0.0, None, Some(GroupConstraintSpec::Centroid { x: *x, y: *y, stiffness: *stiffness })),
+                _ => return Err(SceneError::Unrepresentable(connector.name.clone())),
+            };
+            let constraints = connector.constraints.iter().flatten()
+                .map(|node| index(node, &connector.name)).collect::<Result<Vec<_>, _>>()?;
+            interconnectors.push(InterConnectorSpec {
+                node: index(&connector.node, &connector.name)?,
+                constraints,
+                spacing,
+                spring,
+                tear: tear.map(TearSpec::from),
+                constraint,
+                name: connector.name.clone(),
+                tags: connector.tags.iter().cloned().collect(),
+            });
+        }
+
+        let mut selfconnectors = vec!();
+        for connector in &mesh.selfconnectors {
+            let constraint = match connector.constraint::<Constraint>() {
+                Some(Constraint::Gravity { gravity, delta }) => SelfConstraintSpec::Gravity { gravity: *gravity, delta: *delta },
+                Some(Constraint::BoundGravity { gravity, boundary, delta }) => SelfConstraintSpec::BoundGravity { gravity: *gravity, boundary: *boundary, delta: *delta },
+                Some(Constraint::GroundBoundary { boundary }) => SelfConstraintSpec::GroundBoundary { boundary: *boundary },
+                Some(Constraint::Force { horz_strength, vert_strength, delta }) => SelfConstraintSpec::Impetus { x_force: *horz_strength, y_force: *vert_strength, delta: *delta },
+                Some(Constraint::Wind { horz_strength, vert_strength, accelerated, seed, .. }) =>
+                    SelfConstraintSpec::Wind { horz_strength: *horz_strength, vert_strength: *vert_strength, accelerated: *accelerated, seed: Some(*seed) },
+                _ => return Err(SceneError::Unrepresentable(connector.name.clone())),
+            };
+            selfconnectors.push(SelfConnectorSpec {
+                node: index(&connector.node, &connector.name)?,
+                name: connector.name.clone(),
+                constraint,
+            });
+        }
+
+        let damping = mesh.damping();
+        Ok(Scene { seed: 0, damping: damping.linear, drag: damping.drag, nodes, interconnectors, selfconnectors })
+    }
+
+    pub fn to_json(&self) -> Result<String, SceneError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SceneError> {
+        Ok(fs::write(path, self.to_json()?)?)
+    }
+}
+
+pub fn load_mesh<V: VerletNode, P: AsRef<Path>>(path: P) -> Result<Mesh<V>, SceneError> {
+    Scene::from_file(path)?.to_mesh()
+}
+
+pub fn save_mesh<V: VerletNode, P: AsRef<Path>>(mesh: &Mesh<V>, path: P) -> Result<(), SceneError> {
+    Scene::from_mesh(mesh)?.to_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nac::StepContext;
+    use crate::verlet::{Verlet, Verlet3};
+
+    const GROUPS: &str = r#"{
+        "nodes": [{ "x": 0.0, "y": 0.0 }, { "x": 1.0, "y": 0.0 }, { "x": 0.0, "y": 1.0 }],
+        "interconnectors": [
+            { "node": 0, "constraints": [1], "spacing": 1.0, "spring": 0.5, "name": "edge" },
+            { "node": 0, "constraints": [1, 2], "constraint": { "type": "angle", "angle": 1.5, "stiffness": 0.5 }, "name": "corner" },
+            { "node": 0, "constraints": [1, 2], "constraint": { "type": "area", "area": 0.5, "stiffness": 0.25 }, "name": "face" },
+            { "node": 0, "constraints": [1, 2], "constraint": { "type": "centroid", "x": 0.5, "y": 0.5, "stiffness": 1.0 }, "name": "middle" }
+        ]
+    }"#;
+
+    #[test]
+    fn group_connectors_survive_a_round_trip() {
+        let mesh: Mesh<Verlet> = Scene::from_json(GROUPS).unwrap().to_mesh().unwrap();
+        assert!(matches!(mesh.interconnectors[1].relation(), InterRelation::Group(_)));
+
+        let saved = Scene::from_mesh(&mesh).unwrap();
+        let reloaded: Mesh<Verlet> = Scene::from_json(&saved.to_json().unwrap()).unwrap().to_mesh().unwrap();
+        assert!(matches!(reloaded.interconnectors[0].constraint::<Constraint>(), Some(Constraint::Distance { spacing, .. }) if *spacing == 1.0));
+        assert!(matches!(reloaded.interconnectors[1].constraint::<Constraint>(), Some(Constraint::Angle { angle, .. }) if *angle == 1.5));
+        assert!(matches!(reloaded.interconnectors[2].constraint::<Constraint>(), Some(Constraint::Area { area, .. }) if *area == 0.5));
+        assert!(matches!(reloaded.interconnectors[3].constraint::<Constraint>(), Some(Constraint::Centroid { x, y, .. }) if *x == 0.5 && *y == 0.5));
+        assert!(matches!(reloaded.interconnectors[3].relation(), InterRelation::Group(_)));
+        assert_eq!(reloaded.interconnectors[3].constraints.as_ref().map(Vec::len), Some(2));
+    }
+
+    const CLOTH: &str = r#"{
+        "seed": 7,
+        "damping": 0.5,
+        "nodes": [{ "x": 0.0, "y": 0.0, "pinned": true }, { "x": 5.0, "y": 0.0, "z": 1.0, "mass": 2.0 }],
+        "interconnectors": [{ "node": 1, "constraints": [0], "spacing": 5.0, "spring": 0.5, "name": "cloth", "tags": ["top"],
+                              "tear": { "type": "ratio", "ratio": 1.5 } }],
+        "selfconnectors": [
+            { "node": 1, "name": "gravity", "constraint": { "type": "gravity", "gravity": 120 } },
+            { "node": 1, "name": "wind", "constraint": { "type": "wind", "horz_strength": 3.0, "vert_strength": 0.0 } }
+        ]
+    }"#;
+
+    #[test]
+    fn saved_meshes_load_back_the_same() {
+        let mesh: Mesh<Verlet3> = Scene::from_json(CLOTH).unwrap().to_mesh().unwrap();
+        let path = std::env::temp_dir().join(format!("nac-scene-{}.json", std::process::id()));
+        save_mesh(&mesh, &path).unwrap();
+        let loaded: Mesh<Verlet3> = load_mesh(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let node = loaded.nodes[1].read().unwrap().data;
+        assert_eq!((node.position.x, node.position.y, node.position.z), (5.0, 0.0, 1.0));
+        assert_eq!(node.mass(), 2.0);
+        assert!(loaded.nodes[0].read().unwrap().data.is_pinned());
+        assert_eq!(loaded.damping().linear, 0.5);
+
+        let connector = &loaded.interconnectors[0];
+        assert_eq!(connector.name, "cloth");
+        assert!(connector.tags.contains("top"));
+        assert!(matches!(connector.constraint::<Constraint>(),
+            Some(Constraint::Distance { spacing, spring, tear: Some(Tear::Ratio(ratio)) }) if *spacing == 5.0 && *spring == 0.5 && *ratio == 1.5));
+        assert!(matches!(loaded.selfconnectors[0].constraint::<Constraint>(), Some(Constraint::Gravity { gravity, delta: None }) if *gravity == 120.0));
+        // wind keeps the scene seed it was drawn from
+        assert!(matches!(loaded.selfconnectors[1].constraint::<Constraint>(),
+            Some(Constraint::Wind { horz_strength, seed: 7, accelerated: false, .. }) if *horz_strength == 3.0));
+    }
+
+    #[test]
+    fn closures_have_no_scene_form() {
+        let nodes: Vec<SharedNode<Verlet>> = (0..2).map(|i| Arc::new(RwLock::new(Node::new(Verlet::new(i as f64, 0.0))))).collect();
+        let connector = InterConnector::stepped(Arc::clone(&nodes[0]), Some(vec!(Arc::clone(&nodes[1]))),
+            Arc::new(|a: &Node<Verlet>, b: &Node<Verlet>, _context: &StepContext| (*a, *b))).with_name("custom");
+        let mesh = Mesh::new(nodes, vec!(connector), vec!());
+        assert!(matches!(Scene::from_mesh(&mesh), Err(SceneError::Unrepresentable(name)) if name == "custom"));
+    }
+}
@@ -0,0 +1,263 @@
+use crate::nac::{Node, SharedNode, InterConnector, SelfConnector, InterRelation, SelfStepResolve, Mesh, Solver, FixedStep, JacobiPass, IntegrationPass, read_node, write_node};
+use crate::error::NacError;
+use crate::poison::PoisonPolicy;
+use crate::damping::Damping;
+use crate::handle::{Slots, NodeHandle, InterHandle, SelfHandle};
+
+use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, BTreeSet};
+use std::any::Any;
+
+// Full simulation state of a Mesh at one instant: node data, connectors by node index,
+// elapsed time, stepping and handle bookkeeping. Restoring brings back removed nodes
+// and connectors and makes handles that were valid at snapshot time valid again.
+// Selfconnector relations with state of their own (see `SelfStepResolve::fork`) are copied
+// when the snapshot is taken and copied again on every restore, so a wind generator rewinds
+// with the nodes and meshes built from one snapshot draw their gusts independently. Other
+// relations are kept by reference.
+pub struct MeshSnapshot<T: Sync + Send + 'static> {
+    pub nodes: Vec<Node<T>>,
+    // nodes past this index were only reachable through connectors, not Mesh.nodes
+    pub mesh_node_count: usize,
+    pub interconnectors: Vec<InterConnectorSnapshot<T>>,
+    pub selfconnectors: Vec<SelfConnectorSnapshot<T>>,
+    pub elapsed: f64,
+    pub fixed_step: FixedStep,
+    pub solver: Solver,
+    pub poison_policy: PoisonPolicy,
+    pub damping: Damping,
+    jacobi: Option<JacobiPass<T>>,
+    integration: Option<IntegrationPass<T>>,
+    node_slots: Slots<NodeHandle>,
+    interconnector_slots: Slots<InterHandle>,
+    selfconnector_slots: Slots<SelfHandle>,
+    // the nodes the snapshot was taken from, restored in place so outside references stay live
+    shared: Vec<SharedNode<T>>,
+}
+
+pub struct InterConnectorSnapshot<T: Sync + Send + 'static> {
+    pub name: String,
+    pub tags: BTreeSet<String>,
+    pub enabled: bool,
+    pub node: usize,
+    pub constraints: Option<Vec<usize>>,
+    relation: InterRelation<T>,
+}
+
+pub struct SelfConnectorSnapshot<T: Sync + Send + 'static> {
+    pub name: String,
+    pub node: usize,
+    relation: Arc<dyn SelfStepResolve<T>>,
+}
+
+// State already copied while forking relations, by the address of the original, so
+// relations that shared state share the copy
+#[derive(Default)]
+pub struct Forks {
+    copies: HashMap<usize, Box<dyn Any + Send + Sync>>,
+}
+
+impl Forks {
+    pub fn fork<S: Send + Sync + 'static>(&mut self, state: &Arc<S>, copy: impl FnOnce(&S) -> S) -> Arc<S> {
+        let key = Arc::as_ptr(state) as usize;
+        if let Some(forked) = self.copies.get(&key).and_then(|forked| forked.downcast_ref::<Arc<S>>()) {
+            return Arc::clone(forked);
+        }
+        let forked = Arc::new(copy(state));
+        self.copies.insert(key, Box::new(Arc::clone(&forked)));
+        forked
+    }
+}
+
+pub(crate) fn fork_relation<T: Sync + Send + 'static>(relation: &Arc<dyn SelfStepResolve<T>>, forks: &mut Forks) -> Arc<dyn SelfStepResolve<T>> {
+    relation.fork(forks).unwrap_or_else(|| Arc::clone(relation))
+}
+
+struct Indexer<T: Sync + Send + 'static> {
+    nodes: Vec<Node<T>>,
+    shared: Vec<SharedNode<T>>,
+    indices: HashMap<usize, usize>,
+}
+
+impl<T: Sync + Send + Clone + 'static> Indexer<T> {
+    fn index(&mut self, node: &SharedNode<T>) -> Result<usize, NacError> {
+        let key = Arc::as_ptr(node) as usize;
+        if let Some(&idx) = self.indices.get(&key) {
+            return Ok(idx);
+        }
+        let idx = self.shared.len();
+        self.nodes.push(read_node(node)?.clone());
+        self.shared.push(Arc::clone(node));
+        self.indices.insert(key, idx);
+        Ok(idx)
+    }
+}
+
+impl<T: Sync + Send + Clone + 'static> MeshSnapshot<T> {
+    pub fn from_mesh(mesh: &Mesh<T>) -> Result<Self, NacError> {
+        let mut indexer = Indexer {
+            nodes: Vec::with_capacity(mesh.nodes.len()),
+            shared: Vec::with_capacity(mesh.nodes.len()),
+            indices: HashMap::with_capacity(mesh.nodes.len()),
+        };
+        for node in &mesh.nodes {
+            indexer.index(node)?;
+        }
+        let mesh_node_count = indexer.shared.len();
+
+        let interconnectors = mesh.interconnectors.iter().map(|connector|
+            Ok(InterConnectorSnapshot {
+                name: connector.name.clone(),
+                tags: connector.tags.clone(),
+                enabled: connector.enabled,
+                node: indexer.index(&connector.node)?,
+                constraints: match &connector.constraints {
+                    Some(constraints) => Some(constraints.iter().map(|constraint| indexer.index(constraint)).collect::<Result<_, _>>()?),
+                    None => None,
+                },
+                relation: connector.relation().clone(),
+            })).collect::<Result<_, NacError>>()?;
+
+        let mut forks = Forks::default();
+        let selfconnectors = mesh.selfconnectors.iter().map(|connector|
+            Ok(SelfConnectorSnapshot {
+                name: connector.name.clone(),
+                node: indexer.index(&connector.node)?,
+                relation: fork_relation(connector.relation(), &mut forks),
+            })).collect::<Result<_, NacError>>()?;
+
+        Ok(Self {
+            nodes: indexer.nodes,
+            mesh_node_count,
+            interconnectors,
+            selfconnectors,
+            elapsed: mesh.elapsed,
+            fixed_step: mesh.fixed_step,
+            solver: mesh.solver,
+            poison_policy: mesh.poison_policy,
+            damping: mesh.damping,
+            jacobi: mesh.jacobi,
+            integration: mesh.integration.clone(),
+            node_slots: mesh.node_slots.clone(),
+            interconnector_slots: mesh.interconnector_slots.clone(),
+            selfconnector_slots: mesh.selfconnector_slots.clone(),
+            shared: indexer.shared,
+        })
+    }
+
+    // rebuild the mesh topology around `shared`, one lock per snapshot node
+    fn rebuild(&self, mesh: &mut Mesh<T>, shared: &[SharedNode<T>]) {
+        mesh.nodes = shared[..self.mesh_node_count].to_vec();
+        mesh.interconnectors = self.interconnectors.iter().map(|connector| {
+            let mut rebuilt = InterConnector::with_relation(
+                Arc::clone(&shared[connector.node]),
+                connector.constraints.as_ref().map(|constraints|
+                    constraints.iter().map(|&constraint| Arc::clone(&shared[constraint])).collect()),
+                connector.relation.clone());
+            rebuilt.name = connector.name.clone();
+            rebuilt.tags = connector.tags.clone();
+            rebuilt.enabled = connector.enabled;
+            rebuilt
+        }).collect();
+        let mut forks = Forks::default();
+        mesh.selfconnectors = self.selfconnectors.iter().map(|connector|
+            SelfConnector::stepped(&connector.name, Arc::clone(&shared[connector.node]), fork_relation(&connector.relation, &mut forks)))
+            .collect();
+        mesh.elapsed = self.elapsed;
+        mesh.fixed_step = self.fixed_step;
+        mesh.solver = self.solver;
+        mesh.poison_policy = self.poison_policy;
+        mesh.damping = self.damping;
+        mesh.colouring = None;
+        mesh.jacobi = self.jacobi;
+        mesh.integration = self.integration.clone();
+        mesh.node_slots = self.node_slots.clone();
+        mesh.interconnector_slots = self.interconnector_slots.clone();
+        mesh.selfconnector_slots = self.selfconnector_slots.clone();
+    }
+
+    // a new mesh with its own nodes, independent of the one the snapshot came from
+    pub fn to_mesh(&self) -> Mesh<T> {
+        let shared: Vec<SharedNode<T>> = self.nodes.iter()
+            .map(|node| Arc::new(RwLock::new(node.clone())))
+            .collect();
+        let mut mesh = Mesh::new(vec!(), vec!(), vec!());
+        self.rebuild(&mut mesh, &shared);
+        mesh
+    }
+}
+
+impl<T: Sync + Send + Clone + 'static> Mesh<T> {
+    pub fn snapshot(&self) -> Result<MeshSnapshot<T>, NacError> {
+        MeshSnapshot::from_mesh(self)
+    }
+
+    // write the snapshot's state back into the nodes it was taken from
+    pub fn restore(&mut self, snapshot: &MeshSnapshot<T>) -> Result<(), NacError> {
+        for (shared, state) in snapshot.shared.iter().zip(snapshot.nodes.iter()) {
+            *write_node(shared)? = state.clone();
+        }
+        snapshot.rebuild(self, &snapshot.shared);
+        Ok(())
+    }
+
+    pub fn from_snapshot(snapshot: &MeshSnapshot<T>) -> Self {
+        snapshot.to_mesh()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraint;
+    use crate::verlet::Verlet;
+
+    fn windy() -> Mesh<Verlet> {
+        let nodes: Vec<SharedNode<Verlet>> = (0..2).map(|i| Arc::new(RwLock::new(Node::new(Verlet::new(i as f64, 0.0))))).collect();
+        // both connectors draw from one generator
+        let wind: Arc<dyn SelfStepResolve<Verlet>> = Arc::new(Constraint::wind(0.5, 0.5, 7));
+        let selfconnectors = nodes.iter()
+            .map(|node| SelfConnector::stepped("wind", Arc::clone(node), Arc::clone(&wind)))
+            .collect();
+        Mesh::new(nodes, vec!(), selfconnectors)
+    }
+
+    fn positions(mesh: &Mesh<Verlet>) -> Vec<(f64, f64)> {
+        mesh.nodes.iter().map(|node| {
+            let position = node.read().unwrap().data.position;
+            (position.x, position.y)
+        }).collect()
+    }
+
+    fn run(mesh: &mut Mesh<Verlet>) -> Vec<(f64, f64)> {
+        for _ in 0..5 {
+            mesh.update(0.016, 1).unwrap();
+        }
+        positions(mesh)
+    }
+
+    #[test]
+    fn restoring_rewinds_nodes_and_wind() {
+        let mut mesh = windy();
+        let snapshot = mesh.snapshot().unwrap();
+        let start = positions(&mesh);
+        let first = run(&mut mesh);
+        assert_ne!(first, start);
+
+        mesh.restore(&snapshot).unwrap();
+        assert_eq!(positions(&mesh), start);
+        assert_eq!(run(&mut mesh), first);
+    }
+
+    #[test]
+    fn meshes_from_one_snapshot_blow_alike_and_apart() {
+        let mut mesh = windy();
+        let snapshot = mesh.snapshot().unwrap();
+        let mut copy = Mesh::from_snapshot(&snapshot);
+        let first = run(&mut mesh);
+
+        // the copy has its own nodes and generator, so running the original moved neither
+        assert_eq!(positions(&copy), positions(&snapshot.to_mesh()));
+        assert_eq!(run(&mut copy), first);
+    }
+}
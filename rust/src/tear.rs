@@ -0,0 +1,142 @@
+use crate::nac::Mesh;
+use crate::handle::{NodeHandle, InterHandle};
+use crate::error::NacError;
+
+use std::sync::Arc;
+use std::os::raw::c_void;
+
+// An interconnector a step tore out of the mesh, as it was just before it was removed
+#[derive(Debug,Clone,PartialEq)]
+pub struct Break {
+    // stale by the time the break is reported
+    pub interconnector: InterHandle,
+    pub name: String,
+    // None for nodes only reached through connectors
+    pub node: Option<NodeHandle>,
+    pub constraints: Vec<Option<NodeHandle>>,
+}
+
+pub type OnBreak = Arc<dyn Fn(&Break) + Send + Sync>;
+
+impl<T: Sync + Send + 'static> Mesh<T> {
+    // Called once for each interconnector torn out during an update, after the step's passes
+    // and in interconnector order. Replaces any callback set before.
+    pub fn set_break_callback<F>(&mut self, callback: F)
+        where F: Fn(&Break) + Send + Sync + 'static
+    {
+        self.on_break = Some(Arc::new(callback));
+    }
+
+    pub fn clear_break_callback(&mut self) {
+        self.on_break = None;
+    }
+
+    // Removes every enabled interconnector whose relation says it broke. Connectors on
+    // quarantined nodes are left for when they are released.
+    pub(crate) fn tear(&mut self) -> Result<(), NacError> {
+        let mut broken = vec!();
+        for (idx, connector) in self.interconnectors.iter().enumerate() {
+            if !connector.enabled || self.holds(connector) {
+                continue;
+            }
+            if connector.is_broken().map_err(|err| self.blame(err, &connector.members()))? {
+                broken.push(idx);
+            }
+        }
+        if broken.is_empty() {
+            return Ok(());
+        }
+
+        let breaks: Vec<Break> = broken.iter().map(|&idx| self.break_at(idx)).collect();
+        for &idx in broken.iter().rev() {
+            self.remove_interconnector_at(idx);
+        }
+        for torn in &breaks {
+            info!(self.logger, "interconnector torn"; "slot" => torn.interconnector.slot, "name" => &torn.name);
+            if let Some(on_break) = &self.on_break {
+                on_break(torn);
+            }
+        }
+        Ok(())
+    }
+
+    fn break_at(&self, idx: usize) -> Break {
+        let connector = &self.interconnectors[idx];
+        let handle = |node| self.nodes.iter().position(|candidate| Arc::ptr_eq(candidate, node))
+            .and_then(|idx| self.node_handle(idx));
+        Break {
+            interconnector: self.interconnector_handle(idx).unwrap_or_default(),
+            name: connector.name.clone(),
+            node: handle(&connector.node),
+            constraints: connector.constraints.iter().flatten().map(handle).collect(),
+        }
+    }
+}
+
+// Receives each break from the C API: the torn interconnector's handle, its primary node,
+// its constraint nodes and how many there are, and the user data it was registered with.
+// Nodes outside the mesh come as null handles. The array is only valid during the call.
+pub type BreakCallback = unsafe extern "C" fn(InterHandle, NodeHandle, *const NodeHandle, usize, *mut c_void);
+
+pub(crate) struct CBreakCallback {
+    callback: BreakCallback,
+    user_data: *mut c_void,
+}
+
+// breaks are reported from the thread that updates the mesh, the caller vouches for the user data
+unsafe impl Send for CBreakCallback {}
+unsafe impl Sync for CBreakCallback {}
+
+impl CBreakCallback {
+    pub(crate) fn new(callback: BreakCallback, user_data: *mut c_void) -> Self {
+        Self { callback, user_data }
+    }
+
+    pub(crate) fn call(&self, torn: &Break) {
+        let constraints: Vec<NodeHandle> = torn.constraints.iter().map(|node| node.unwrap_or_default()).collect();
+        unsafe {
+            (self.callback)(torn.interconnector, torn.node.unwrap_or_default(), constraints.as_ptr(), constraints.len(), self.user_data)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nac::{Node, SharedNode, InterConnector};
+    use crate::constraint::{Constraint, Tear};
+    use crate::verlet::Verlet;
+
+    use std::sync::{Mutex, RwLock};
+
+    fn link(a: &SharedNode<Verlet>, b: &SharedNode<Verlet>, tear: Tear) -> InterConnector<Verlet> {
+        InterConnector::stepped(Arc::clone(a), Some(vec!(Arc::clone(b))), Arc::new(Constraint::Distance { spacing: 1.0, spring: 0.01, tear: Some(tear) }))
+    }
+
+    #[test]
+    fn overstretched_connectors_tear_and_are_reported() {
+        // a weak spring leaves the far pair stretched past both kinds of limit
+        let nodes: Vec<SharedNode<Verlet>> = [0.0, 1.0, 4.0, 5.6].iter().map(|&x| Arc::new(RwLock::new(Node::new(Verlet::new(x, 0.0))))).collect();
+        let interconnectors = vec!(
+            link(&nodes[0], &nodes[1], Tear::Ratio(1.5)).with_name("held"),
+            link(&nodes[1], &nodes[2], Tear::Ratio(1.5)).with_name("ratio"),
+            link(&nodes[2], &nodes[3], Tear::Distance(1.5)).with_name("distance"));
+        let mut mesh = Mesh::new(nodes, interconnectors, vec!());
+        let torn = mesh.interconnector_handle(1).unwrap();
+        let breaks = Arc::new(Mutex::new(vec!()));
+        let reported = Arc::clone(&breaks);
+        mesh.set_break_callback(move |torn: &Break| reported.lock().unwrap().push(torn.clone()));
+
+        mesh.update(0.016, 1).unwrap();
+        assert_eq!(mesh.interconnectors.len(), 1);
+        assert_eq!(mesh.interconnectors[0].name, "held");
+        assert!(mesh.interconnector(torn).is_none());
+
+        let breaks = breaks.lock().unwrap();
+        let names: Vec<&str> = breaks.iter().map(|torn| torn.name.as_str()).collect();
+        assert_eq!(names, vec!("ratio", "distance"));
+        assert_eq!(breaks[0].interconnector, torn);
+        assert_eq!(breaks[0].node, mesh.node_handle(1));
+        assert_eq!(breaks[0].constraints, vec!(mesh.node_handle(2)));
+    }
+}
@@ -1,8 +1,14 @@
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
 
-use crate::nac::Node;
+use std::sync::{Arc, Mutex};
+use std::fmt;
 
-#[derive(Debug,Builder,Default,Clone,Copy)]
+use crate::nac::{Node, Residual, JacobiCorrection, Kinematic, SelfStepResolve, StepContext};
+use crate::constraint::Constraint;
+use crate::damping::Damping;
+
+#[derive(Debug,Builder,Clone,Copy)]
 #[builder(setter(into))]
 #[repr(C)]
 pub struct Verlet {
@@ -11,6 +17,21 @@ pub struct Verlet {
     delta_position: Point2,
     #[builder(default="false")]
     pub pinned: bool,
+    #[builder(default="1.0")]
+    pub mass: f64,
+    // rate, per second, this node's velocity decays at on top of the mesh's damping
+    #[builder(default="0.0")]
+    pub damping: f64,
+    // how long the step from delta_position to position took, 0 until the node is first stepped
+    #[builder(default="0.0")]
+    last_step: f64,
+}
+
+// at rest at the origin, with a mass of 1
+impl Default for Verlet {
+    fn default() -> Self {
+        Verlet::from_parts(Point2::default(), Point2::default(), false)
+    }
 }
 
 impl VerletBuilder {
@@ -31,21 +52,360 @@ impl Verlet {
             .unwrap()
     }
 
-    fn updated(x: f64, y: f64, dx: f64, dy: f64) -> Self {
+    pub fn new_pinned(x: f64, y: f64) -> Self {
         VerletBuilder::default()
             .position(Point2::new(x,y))
-            .delta_position(Point2::new(dx,dy))
+            .pinned(true)
             .build()
             .unwrap()
     }
 
-    pub fn new_pinned(x: f64, y: f64) -> Self {
+    pub fn from_parts(position: Point2, delta_position: Point2, pinned: bool) -> Self {
         VerletBuilder::default()
-            .position(Point2::new(x,y))
-            .pinned(true)
+            .position(position)
+            .delta_position(delta_position)
+            .pinned(pinned)
+            .build()
+            .unwrap()
+    }
+
+    pub fn delta_position(&self) -> Point2 {
+        self.delta_position
+    }
+
+    pub fn last_step(&self) -> f64 {
+        self.last_step
+    }
+}
+
+// Three dimensional counterpart of Verlet, for meshes draped in space rather than the plane
+#[derive(Debug,Builder,Clone,Copy)]
+#[builder(setter(into))]
+#[repr(C)]
+pub struct Verlet3 {
+    pub position: Point3,
+    #[builder(default="Point3::default()")]
+    delta_position: Point3,
+    #[builder(default="false")]
+    pub pinned: bool,
+    #[builder(default="1.0")]
+    pub mass: f64,
+    #[builder(default="0.0")]
+    pub damping: f64,
+    #[builder(default="0.0")]
+    last_step: f64,
+}
+
+impl Default for Verlet3 {
+    fn default() -> Self {
+        Verlet3::from_parts(Point3::default(), Point3::default(), false)
+    }
+}
+
+impl Verlet3 {
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        VerletNode::new_at(Point3::new(x, y, z))
+    }
+
+    pub fn new_pinned(x: f64, y: f64, z: f64) -> Self {
+        VerletNode::new_pinned_at(Point3::new(x, y, z))
+    }
+
+    pub fn from_parts(position: Point3, delta_position: Point3, pinned: bool) -> Self {
+        Verlet3Builder::default()
+            .position(position)
+            .delta_position(delta_position)
+            .pinned(pinned)
             .build()
             .unwrap()
     }
+
+    pub fn delta_position(&self) -> Point3 {
+        self.delta_position
+    }
+
+    pub fn last_step(&self) -> f64 {
+        self.last_step
+    }
+}
+
+// Coordinates the Verlet constraints work in. Gravity and the ground act along y, wind and
+// forces in the xy plane, and angle, area and centroid constraints on the xy projection,
+// leaving z as it is. Two dimensional points have no z and drop any they are given.
+pub trait Point: Copy + Default + PartialEq + fmt::Debug + Send + Sync + 'static {
+    fn from_xyz(x: f64, y: f64, z: f64) -> Self;
+    fn x(&self) -> f64;
+    fn y(&self) -> f64;
+    fn z(&self) -> f64;
+
+    fn planar(x: f64, y: f64) -> Self {
+        Self::from_xyz(x, y, 0.0)
+    }
+
+    // the same point moved to (x, y) in the plane
+    fn with_xy(&self, x: f64, y: f64) -> Self {
+        Self::from_xyz(x, y, self.z())
+    }
+
+    fn add(&self, other: Self) -> Self {
+        Self::from_xyz(self.x() + other.x(), self.y() + other.y(), self.z() + other.z())
+    }
+
+    fn sub(&self, other: Self) -> Self {
+        Self::from_xyz(self.x() - other.x(), self.y() - other.y(), self.z() - other.z())
+    }
+
+    fn scale(&self, by: f64) -> Self {
+        Self::from_xyz(self.x() * by, self.y() * by, self.z() * by)
+    }
+
+    fn length(&self) -> f64 {
+        (self.x().powi(2) + self.y().powi(2) + self.z().powi(2)).sqrt()
+    }
+}
+
+// The node types the built-in constraints, Cloth generators and integrators work on
+pub trait VerletNode: Copy + Default + Sync + Send + 'static {
+    type Point: Point;
+
+    // a node of mass 1
+    fn from_parts(position: Self::Point, delta_position: Self::Point, pinned: bool) -> Self;
+    // this node moved, keeping its mass
+    fn with_parts(&self, position: Self::Point, delta_position: Self::Point, pinned: bool) -> Self;
+    // panics unless mass is positive and finite
+    fn with_mass(&self, mass: f64) -> Self;
+    fn with_damping(&self, damping: f64) -> Self;
+    fn with_last_step(&self, last_step: f64) -> Self;
+    fn point(&self) -> Self::Point;
+    // where the node was a step ago
+    fn previous_point(&self) -> Self::Point;
+    fn is_pinned(&self) -> bool;
+    fn mass(&self) -> f64;
+    fn damping(&self) -> f64;
+    // how long the node took to get from previous_point to point, 0 if not known
+    fn last_step(&self) -> f64;
+
+    // How far the node would travel over a step of `delta` at the velocity it carries. The
+    // distance it came over its last step is scaled to the new step, so a change of timestep
+    // neither adds energy nor takes it away. Constraint corrections are scaled with it, so
+    // steps several times longer than the last can still overshoot.
+    fn carried(&self, delta: f64) -> Self::Point {
+        let travelled = self.point().sub(self.previous_point());
+        let last_step = self.last_step();
+        if last_step > 0.0 && delta != last_step {
+            travelled.scale(delta / last_step)
+        } else {
+            travelled
+        }
+    }
+
+    // pinned nodes weigh infinitely much, and take none of a correction
+    fn inverse_mass(&self) -> f64 {
+        if self.is_pinned() {
+            0.0
+        } else {
+            1.0 / self.mass()
+        }
+    }
+
+    // at rest at `position`
+    fn new_at(position: Self::Point) -> Self {
+        Self::from_parts(position, position, false)
+    }
+
+    fn new_pinned_at(position: Self::Point) -> Self {
+        Self::from_parts(position, Self::Point::default(), true)
+    }
+}
+
+impl VerletNode for Verlet {
+    type Point = Point2;
+
+    fn from_parts(position: Point2, delta_position: Point2, pinned: bool) -> Self {
+        Verlet::from_parts(position, delta_position, pinned)
+    }
+
+    fn with_parts(&self, position: Point2, delta_position: Point2, pinned: bool) -> Self {
+        Verlet { position, delta_position, pinned, ..*self }
+    }
+
+    fn with_mass(&self, mass: f64) -> Self {
+        assert!(mass > 0.0 && mass.is_finite(), "node mass must be positive and finite, got {}", mass);
+        Verlet { mass, ..*self }
+    }
+
+    fn with_damping(&self, damping: f64) -> Self {
+        Verlet { damping, ..*self }
+    }
+
+    fn with_last_step(&self, last_step: f64) -> Self {
+        Verlet { last_step, ..*self }
+    }
+
+    fn point(&self) -> Point2 {
+        self.position
+    }
+
+    fn previous_point(&self) -> Point2 {
+        self.delta_position
+    }
+
+    fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    fn damping(&self) -> f64 {
+        self.damping
+    }
+
+    fn last_step(&self) -> f64 {
+        self.last_step
+    }
+}
+
+impl VerletNode for Verlet3 {
+    type Point = Point3;
+
+    fn from_parts(position: Point3, delta_position: Point3, pinned: bool) -> Self {
+        Verlet3::from_parts(position, delta_position, pinned)
+    }
+
+    fn with_parts(&self, position: Point3, delta_position: Point3, pinned: bool) -> Self {
+        Verlet3 { position, delta_position, pinned, ..*self }
+    }
+
+    fn with_mass(&self, mass: f64) -> Self {
+        assert!(mass > 0.0 && mass.is_finite(), "node mass must be positive and finite, got {}", mass);
+        Verlet3 { mass, ..*self }
+    }
+
+    fn with_damping(&self, damping: f64) -> Self {
+        Verlet3 { damping, ..*self }
+    }
+
+    fn with_last_step(&self, last_step: f64) -> Self {
+        Verlet3 { last_step, ..*self }
+    }
+
+    fn point(&self) -> Point3 {
+        self.position
+    }
+
+    fn previous_point(&self) -> Point3 {
+        self.delta_position
+    }
+
+    fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    fn damping(&self) -> f64 {
+        self.damping
+    }
+
+    fn last_step(&self) -> f64 {
+        self.last_step
+    }
+}
+
+impl<V: VerletNode> Residual for V {
+    fn residual(&self, updated: &Self) -> f64 {
+        updated.point().sub(self.point()).length()
+    }
+}
+
+impl<V: VerletNode> JacobiCorrection for V {
+    type Correction = V::Point;
+
+    fn correction(&self, updated: &Self) -> V::Point {
+        updated.point().sub(self.point())
+    }
+
+    fn accumulate(total: V::Point, correction: V::Point) -> V::Point {
+        total.add(correction)
+    }
+
+    fn corrected(&self, total: V::Point, count: usize) -> Self {
+        if self.is_pinned() || count == 0 {
+            return *self;
+        }
+        self.with_parts(self.point().add(divided(total, count as f64)), self.previous_point(), false)
+    }
+}
+
+impl<V: VerletNode> Kinematic for V {
+    type Vector = V::Point;
+
+    fn position(&self) -> V::Point {
+        self.point()
+    }
+
+    fn velocity(&self, dt: f64) -> V::Point {
+        let step = if self.last_step() > 0.0 { self.last_step() } else { dt };
+        if step > 0.0 {
+            divided(self.point().sub(self.previous_point()), step)
+        } else {
+            V::Point::default()
+        }
+    }
+
+    fn moved(&self, position: V::Point, velocity: V::Point, dt: f64) -> Self {
+        if self.is_pinned() {
+            return *self;
+        }
+        self.with_parts(position, position.sub(velocity.scale(dt)), false).with_last_step(dt)
+    }
+
+    fn add(a: V::Point, b: V::Point) -> V::Point {
+        a.add(b)
+    }
+
+    fn scale(a: V::Point, by: f64) -> V::Point {
+        a.scale(by)
+    }
+
+    // gravity_constraint adds half of `gravity` times delta² a step, which is the acceleration
+    // it stands for; a fixed delta keeps that displacement per step whatever dt is. Bound
+    // gravity only accelerates nodes above its boundary, the ground holds up the rest.
+    fn acceleration(relation: &dyn SelfStepResolve<Self>, node: &Self, context: &StepContext) -> Option<V::Point> {
+        let falling = |gravity: f64, delta: Option<f64>| {
+            let scale = match delta {
+                Some(delta) if context.dt > 0.0 => (delta / context.dt).powi(2),
+                _ => 1.0,
+            };
+            V::Point::planar(0.0, 0.5 * gravity * scale)
+        };
+        match relation.as_any()?.downcast_ref::<Constraint>()? {
+            Constraint::Gravity { gravity, delta } => Some(falling(*gravity, *delta)),
+            Constraint::BoundGravity { gravity, boundary, delta } if node.point().y() >= *boundary => Some(falling(*gravity, *delta)),
+            _ => None,
+        }
+    }
+
+    // the boundary still stops nodes the integrator carried through it
+    fn still_resolves(relation: &dyn SelfStepResolve<Self>) -> bool {
+        matches!(relation.as_any().and_then(|relation| relation.downcast_ref::<Constraint>()), Some(Constraint::BoundGravity { .. }))
+    }
+
+    fn resistance(&self, velocity: V::Point, damping: &Damping) -> Option<V::Point> {
+        let linear = damping.linear + self.damping();
+        if self.is_pinned() || (linear == 0.0 && damping.drag == 0.0) {
+            return None;
+        }
+        Some(velocity.scale(-(linear + damping.drag * velocity.length() / self.mass())))
+    }
+}
+
+fn divided<P: Point>(point: P, by: f64) -> P {
+    P::from_xyz(point.x() / by, point.y() / by, point.z() / by)
 }
 
 #[derive(Clone,PartialEq,Debug,Copy,Default)]
@@ -64,120 +424,175 @@ impl Point2 {
     }
 }
 
-type Vector2 = Point2;
-
-// a cloth type constraint function for two nodes
-pub fn internode_constraint(node1: &Node<Verlet>, node2: &Node<Verlet>, spacing: f64, spring: f64) -> (Node<Verlet>,Node<Verlet>) {
-        let diff_x = node1.data.position.x - node2.data.position.x;
-        let diff_y = node1.data.position.y - node2.data.position.y;
-        let dist = (diff_x.powi(2) + diff_y.powi(2)).sqrt();
+impl Point for Point2 {
+    fn from_xyz(x: f64, y: f64, _z: f64) -> Self {
+        Point2::new(x, y)
+    }
 
-        let diff = (spacing - dist) / dist;
+    fn x(&self) -> f64 {
+        self.x
+    }
 
-        // TODO tear distance stuff: destroy all refcounts
+    fn y(&self) -> f64 {
+        self.y
+    }
 
-        let px = diff_x * diff * spring;
-        //println!("x: {:?}, {:?}, {:?}, {:?}", diff_x, diff, spring, px);
-        //println!("pos: {:?}, {:?}", node1.data.position.x, node2.data.position.x);
-        let py = diff_y * diff * spring;
-        //println!("y: {:?}, {:?}, {:?}, {:?}", diff_y, diff, spring, py);
-        //println!("pos: {:?}, {:?}", node1.data.position.y, node2.data.position.y);
+    fn z(&self) -> f64 {
+        0.0
+    }
+}
 
+#[derive(Clone,PartialEq,Debug,Copy,Default)]
+#[repr(C)]
+pub struct Point3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
 
-        let node1_constrained: Node<Verlet>;
-        if node1.data.pinned {
-            node1_constrained = node1.clone();
-        }
-        else
-        {
-            node1_constrained = Node::new(
-                Verlet::updated(
-                    node1.data.position.x + px,
-                    node1.data.position.y + py,
-                    node1.data.delta_position.x,
-                    node1.data.delta_position.y
-                )
-            );
+impl Point3 {
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Point3 {
+            x,
+            y,
+            z
         }
+    }
+}
 
-        let node2_constrained: Node<Verlet>;
-        if node2.data.pinned {
-            node2_constrained = node2.clone();
-        }
-        else
-        {
-            node2_constrained = Node::new(
-                Verlet::updated(
-                    node2.data.position.x - px,
-                    node2.data.position.y - py,
-                    node2.data.delta_position.x,
-                    node2.data.delta_position.y
-                )
-            );
+impl Point for Point3 {
+    fn from_xyz(x: f64, y: f64, z: f64) -> Self {
+        Point3::new(x, y, z)
+    }
+
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+
+    fn z(&self) -> f64 {
+        self.z
+    }
+}
+
+type Vector2 = Point2;
+
+// A cloth type constraint function for two nodes. Each node takes its inverse mass's share,
+// w_i/(w1+w2), of the spring's correction for every free node in the pair: nodes of equal
+// mass both move by the correction, and a node held by a pinned one moves by it alone while
+// the pinned node stays put.
+pub fn internode_constraint<V: VerletNode>(node1: &Node<V>, node2: &Node<V>, spacing: f64, spring: f64) -> (Node<V>,Node<V>) {
+        let (inverse1, inverse2) = (node1.data.inverse_mass(), node2.data.inverse_mass());
+        let total = inverse1 + inverse2;
+        if total <= 0.0 {
+            return (*node1, *node2);
         }
 
+        let diff = node1.data.point().sub(node2.data.point());
+        let dist = diff.length();
+
+        let free = [inverse1, inverse2].iter().filter(|&&inverse| inverse > 0.0).count() as f64;
+        let correction = diff.scale((spacing - dist) / dist).scale(free * spring / total);
+
+        let node1_constrained = moved(node1, node1.data.point().add(correction.scale(inverse1)));
+        let node2_constrained = moved(node2, node2.data.point().sub(correction.scale(inverse2)));
+
         (node1_constrained, node2_constrained)
 }
 
-pub fn gravity_constraint(node: &Node<Verlet>, delta: f64, gravity: i16) -> Node<Verlet> {
+// Time-corrected Verlet: the velocity the node carries is rescaled when `delta` differs
+// from the step that brought it here, see VerletNode::carried
+pub fn gravity_constraint<V: VerletNode>(node: &Node<V>, delta: f64, gravity: f64) -> Node<V> {
+    let position = node.data.point();
+    let velocity = node.data.carried(delta);
     Node::new(
-        Verlet::updated(
-            node.data.position.x + (node.data.position.x - node.data.delta_position.x),
-            node.data.position.y + (node.data.position.y - node.data.delta_position.y) + 0.5 * f64::from(gravity) * delta.powi(2),
-            node.data.position.x,
-            node.data.position.y
-        )
+        node.data.with_parts(
+            position.add(velocity).add(V::Point::planar(0.0, 0.5 * gravity * delta.powi(2))),
+            position,
+            false
+        ).with_last_step(delta)
     )
 }
 
-pub fn force_constraint(node: &Node<Verlet>, delta: f64, horz_strength: f64, vert_strength: f64) -> Node<Verlet> {
-    let delta = delta.powi(2);
+// strengths are forces, accelerating the node by its inverse mass; pinned nodes do not move
+pub fn force_constraint<V: VerletNode>(node: &Node<V>, delta: f64, horz_strength: f64, vert_strength: f64) -> Node<V> {
+    if node.data.is_pinned() {
+        return *node;
+    }
+    let pushed = delta.powi(2) / node.data.mass();
+    let position = node.data.point();
     Node::new(
-        Verlet::updated(
-            node.data.position.x + horz_strength * delta,
-            node.data.position.y + vert_strength * delta,
-            node.data.position.x,
-            node.data.position.y
-        )
+        node.data.with_parts(
+            position.add(V::Point::planar(horz_strength * pushed, vert_strength * pushed)),
+            position,
+            false
+        ).with_last_step(delta)
     )
 }
 
-pub fn wind_constraint(node: &Node<Verlet>, horz_strength: f64, vert_strength: f64) -> Node<Verlet> {
-    let rand_x = horz_strength * rand::thread_rng().gen::<f64>();
+// The node with the velocity it carries into the next step damped over `delta`, by moving
+// where it was a step ago towards where it is. Linear damping decays the velocity
+// exponentially, and drag is taken implicitly so large speeds or steps cannot reverse it.
+pub fn damped<V: VerletNode>(node: &Node<V>, delta: f64, damping: &Damping) -> Node<V> {
+    let linear = damping.linear + node.data.damping();
+    if node.data.is_pinned() || delta <= 0.0 || (linear == 0.0 && damping.drag == 0.0) {
+        return *node;
+    }
+    let position = node.data.point();
+    let travelled = position.sub(node.data.previous_point());
+    let last_step = if node.data.last_step() > 0.0 { node.data.last_step() } else { delta };
+    let speed = travelled.length() / last_step;
+    let retained = (-linear * delta).exp() / (1.0 + damping.drag * speed * delta / node.data.mass());
+    Node::new(node.data.with_parts(position, position.sub(travelled.scale(retained)), false))
+}
+
+// A seeded generator shared by the stochastic constraints that draw from it, so the same
+// seed replays the same gusts. ChaCha8 is a named algorithm, so unlike StdRng its stream
+// stays the same across rand releases and platforms.
+pub type SharedRng = Arc<Mutex<ChaCha8Rng>>;
+
+pub fn seeded_rng(seed: u64) -> SharedRng {
+    Arc::new(Mutex::new(ChaCha8Rng::seed_from_u64(seed)))
+}
+
+// strengths are the furthest a gust moves the node in one step, whatever its length
+pub fn wind_constraint<V: VerletNode, R: Rng + ?Sized>(node: &Node<V>, horz_strength: f64, vert_strength: f64, rng: &mut R) -> Node<V> {
+    let rand_x = horz_strength * rng.gen::<f64>();
     let new_x = if rand_x < horz_strength * 0.5 {
         rand_x
     } else {
         0.0
     };
     //let new_x = horz_strength * rand::thread_rng().gen::<f64>();
-    let rand_y = vert_strength * rand::thread_rng().gen::<f64>();
+    let rand_y = vert_strength * rng.gen::<f64>();
     let new_y = if rand_y < vert_strength * 0.5 {
         rand_y
     } else {
         0.0
     };
     Node::new(
-        Verlet::updated(
-            node.data.position.x + new_x,
-            node.data.position.y + new_y,
-            node.data.delta_position.x,
-            node.data.delta_position.y
+        node.data.with_parts(
+            node.data.point().add(V::Point::planar(new_x, new_y)),
+            node.data.previous_point(),
+            false
         )
     )
 }
 
-pub fn ground_bound_gravity_constraint(node: &Node<Verlet>, delta: f64, gravity: i16, boundary: f64) -> Node<Verlet> {
-    let new_node: Node<Verlet>;
-    if node.data.position.y < boundary
+// strengths are accelerations, so gusts hold up across frame rates and push nodes of any mass alike
+pub fn accelerated_wind_constraint<V: VerletNode, R: Rng + ?Sized>(node: &Node<V>, delta: f64, horz_strength: f64, vert_strength: f64, rng: &mut R) -> Node<V> {
+    let delta = delta.powi(2);
+    wind_constraint(node, horz_strength * delta, vert_strength * delta, rng)
+}
+
+pub fn ground_bound_gravity_constraint<V: VerletNode>(node: &Node<V>, delta: f64, gravity: f64, boundary: f64) -> Node<V> {
+    let new_node: Node<V>;
+    if node.data.point().y() < boundary
     {
-        new_node = Node::new(
-            Verlet::updated(
-                node.data.position.x,
-                boundary,
-                node.data.delta_position.x,
-                node.data.delta_position.y
-            )
-        );
+        new_node = grounded(node, boundary);
     }
     else
     {
@@ -188,30 +603,217 @@ pub fn ground_bound_gravity_constraint(node: &Node<Verlet>, delta: f64, gravity:
 }
 
 
-pub fn ground_boundary_constraint(node: &Node<Verlet>, boundary: f64) -> Node<Verlet> {
-    let new_node: Node<Verlet>;
-    if node.data.position.y < boundary
+pub fn ground_boundary_constraint<V: VerletNode>(node: &Node<V>, boundary: f64) -> Node<V> {
+    let new_node: Node<V>;
+    if node.data.point().y() < boundary
     {
-        new_node = Node::new(
-            Verlet::updated(
-                node.data.position.x,
-                boundary,
-                node.data.delta_position.x,
-                node.data.delta_position.y
-            )
-        );
+        new_node = grounded(node, boundary);
     }
     else
     {
-        new_node = Node::new(
-            Verlet::updated(
-                node.data.position.x,
-                node.data.position.y,
-                node.data.delta_position.x,
-                node.data.delta_position.y
-            )
-        );
+        new_node = Node::new(node.data.with_parts(node.data.point(), node.data.previous_point(), false));
     }
     
     new_node
 }
+
+// the node held at the boundary, keeping its previous position
+fn grounded<V: VerletNode>(node: &Node<V>, boundary: f64) -> Node<V> {
+    let position = node.data.point();
+    Node::new(node.data.with_parts(position.with_xy(position.x(), boundary), node.data.previous_point(), false))
+}
+
+// a node moved to `position` keeping its previous position, or left in place if pinned
+fn moved<V: VerletNode>(node: &Node<V>, position: V::Point) -> Node<V> {
+    if node.data.is_pinned() {
+        *node
+    } else {
+        Node::new(node.data.with_parts(position, node.data.previous_point(), false))
+    }
+}
+
+// a node moved to (x, y) in the plane
+fn moved_xy<V: VerletNode>(node: &Node<V>, x: f64, y: f64) -> Node<V> {
+    moved(node, node.data.point().with_xy(x, y))
+}
+
+// Group constraints take the nodes of one connector at once and return them in the same order.
+// They work in the xy plane.
+
+// Pulls the angle at nodes[0] between nodes[1] and nodes[2] towards `angle` radians by
+// rotating the outer nodes about the vertex; `stiffness` is the share of the error fixed per pass.
+pub fn angle_constraint<V: VerletNode>(nodes: &[&Node<V>], angle: f64, stiffness: f64) -> Vec<Node<V>> {
+    let mut constrained: Vec<Node<V>> = nodes.iter().map(|&&node| node).collect();
+    if nodes.len() < 3 {
+        return constrained;
+    }
+    let (vertex, first, second) = (nodes[0].data.point(), nodes[1].data.point(), nodes[2].data.point());
+    let (ux, uy) = (first.x() - vertex.x(), first.y() - vertex.y());
+    let (vx, vy) = (second.x() - vertex.x(), second.y() - vertex.y());
+    let error = angle_offset(nodes, angle) * stiffness;
+
+    let free = |node: &Node<V>| if node.data.is_pinned() { 0.0 } else { 1.0 };
+    let (first_free, second_free) = (free(nodes[1]), free(nodes[2]));
+    if first_free + second_free == 0.0 {
+        return constrained;
+    }
+    let rotate = |x: f64, y: f64, by: f64| (vertex.x() + x * by.cos() - y * by.sin(), vertex.y() + x * by.sin() + y * by.cos());
+    let (x, y) = rotate(ux, uy, -error * first_free / (first_free + second_free));
+    constrained[1] = moved_xy(nodes[1], x, y);
+    let (x, y) = rotate(vx, vy, error * second_free / (first_free + second_free));
+    constrained[2] = moved_xy(nodes[2], x, y);
+    constrained
+}
+
+// how far the angle at the first node is turned from `angle`, on whichever side it lies
+fn angle_offset<V: VerletNode>(nodes: &[&Node<V>], angle: f64) -> f64 {
+    let (vertex, first, second) = (nodes[0].data.point(), nodes[1].data.point(), nodes[2].data.point());
+    let (ux, uy) = (first.x() - vertex.x(), first.y() - vertex.y());
+    let (vx, vy) = (second.x() - vertex.x(), second.y() - vertex.y());
+    let current = (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let target = if current < 0.0 { -angle } else { angle };
+    target - current
+}
+
+// in radians
+pub fn angle_error<V: VerletNode>(nodes: &[&Node<V>], angle: f64) -> f64 {
+    if nodes.len() < 3 {
+        return 0.0;
+    }
+    angle_offset(nodes, angle).abs()
+}
+
+fn polygon_area<V: VerletNode>(nodes: &[&Node<V>]) -> f64 {
+    let count = nodes.len();
+    (0..count).map(|idx| {
+        let (p, q) = (nodes[idx].data.point(), nodes[(idx + 1) % count].data.point());
+        p.x() * q.y() - q.x() * p.y()
+    }).sum::<f64>().abs() / 2.0
+}
+
+fn centroid<V: VerletNode>(nodes: &[&Node<V>]) -> (f64, f64) {
+    let count = nodes.len() as f64;
+    (
+        nodes.iter().map(|node| node.data.point().x()).sum::<f64>() / count,
+        nodes.iter().map(|node| node.data.point().y()).sum::<f64>() / count,
+    )
+}
+
+// Scales the polygon traced by the nodes about its centroid towards `area`
+pub fn area_constraint<V: VerletNode>(nodes: &[&Node<V>], area: f64, stiffness: f64) -> Vec<Node<V>> {
+    let constrained: Vec<Node<V>> = nodes.iter().map(|&&node| node).collect();
+    if nodes.len() < 3 {
+        return constrained;
+    }
+    let current = polygon_area(nodes);
+    if current <= f64::EPSILON {
+        return constrained;
+    }
+    let (cx, cy) = centroid(nodes);
+    let scale = 1.0 + ((area.abs() / current).sqrt() - 1.0) * stiffness;
+    nodes.iter().map(|node| {
+        let position = node.data.point();
+        moved_xy(node, cx + (position.x() - cx) * scale, cy + (position.y() - cy) * scale)
+    }).collect()
+}
+
+// as a length, the difference between the sides of squares of the two areas
+pub fn area_error<V: VerletNode>(nodes: &[&Node<V>], area: f64) -> f64 {
+    if nodes.len() < 3 {
+        return 0.0;
+    }
+    (area.abs().sqrt() - polygon_area(nodes).sqrt()).abs()
+}
+
+// Moves the free nodes together so the centroid of all of them heads for (x, y)
+pub fn centroid_constraint<V: VerletNode>(nodes: &[&Node<V>], x: f64, y: f64, stiffness: f64) -> Vec<Node<V>> {
+    let free = nodes.iter().filter(|node| !node.data.is_pinned()).count();
+    if free == 0 {
+        return nodes.iter().map(|&&node| node).collect();
+    }
+    let count = nodes.len() as f64;
+    let (cx, cy) = centroid(nodes);
+    let share = stiffness * count / free as f64;
+    let (dx, dy) = ((x - cx) * share, (y - cy) * share);
+    nodes.iter().map(|node| moved(node, node.data.point().add(V::Point::planar(dx, dy)))).collect()
+}
+
+pub fn centroid_error<V: VerletNode>(nodes: &[&Node<V>], x: f64, y: f64) -> f64 {
+    if nodes.is_empty() {
+        return 0.0;
+    }
+    let (cx, cy) = centroid(nodes);
+    (x - cx).hypot(y - cy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nac::{Mesh, SelfConnector};
+
+    use std::sync::RwLock;
+
+    fn gap_closed(node1: Verlet, node2: Verlet) -> (f64, f64) {
+        let (moved1, moved2) = internode_constraint(&Node::new(node1), &Node::new(node2), 1.0, 0.25);
+        ((moved1.data.position.x - node1.position.x).abs(), (moved2.data.position.x - node2.position.x).abs())
+    }
+
+    #[test]
+    fn distance_corrections_are_shared_by_inverse_mass() {
+        // two units apart with a spacing of one: each equal node moves a quarter of the gap
+        assert_eq!(gap_closed(Verlet::new(0.0, 0.0), Verlet::new(2.0, 0.0)), (0.25, 0.25));
+        // a node three times as heavy takes a quarter of the same total
+        assert_eq!(gap_closed(Verlet::new(0.0, 0.0).with_mass(3.0), Verlet::new(2.0, 0.0)), (0.125, 0.375));
+        // a free node held by a pinned one moves as far as either would between free nodes
+        assert_eq!(gap_closed(Verlet::new_pinned(0.0, 0.0), Verlet::new(2.0, 0.0)), (0.0, 0.25));
+    }
+
+    #[test]
+    fn distance_corrections_act_along_z() {
+        let (near, far) = internode_constraint(&Node::new(Verlet3::new(1.0, 1.0, 0.0)), &Node::new(Verlet3::new(1.0, 1.0, 2.0)), 1.0, 0.25);
+        assert_eq!(near.data.position, Point3::new(1.0, 1.0, 0.25));
+        assert_eq!(far.data.position, Point3::new(1.0, 1.0, 1.75));
+    }
+
+    #[test]
+    fn gravity_keeps_a_node_moving_along_z() {
+        // moving one unit per second along z after a step of 0.5
+        let node = Verlet3::new(0.0, 0.0, 0.0).with_parts(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, -0.5), false).with_last_step(0.5);
+        let fallen = gravity_constraint(&Node::new(node), 0.5, 8.0).data;
+        assert_eq!(fallen.position, Point3::new(0.0, 1.0, 0.5));
+        assert_eq!(fallen.delta_position, Point3::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn the_ground_leaves_z_alone() {
+        let node = Verlet3::new(0.0, -1.0, 3.0).with_parts(Point3::new(0.0, -1.0, 3.0), Point3::new(0.0, -0.5, 2.0), false);
+        let grounded = ground_boundary_constraint(&Node::new(node), 0.0).data;
+        assert_eq!(grounded.position, Point3::new(0.0, 0.0, 3.0));
+        assert_eq!(grounded.delta_position, Point3::new(0.0, -0.5, 2.0));
+        // above the boundary, bound gravity falls in y and carries z on
+        let falling = ground_bound_gravity_constraint(&Node::new(node.with_parts(Point3::new(0.0, 1.0, 3.0), Point3::new(0.0, 1.0, 2.0), false)), 1.0, 2.0, 0.0).data;
+        assert_eq!(falling.position, Point3::new(0.0, 2.0, 4.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "positive")]
+    fn nodes_need_a_positive_mass() {
+        Verlet3::new(0.0, 0.0, 0.0).with_mass(0.0);
+    }
+
+    #[test]
+    fn a_coasting_node_keeps_its_velocity_when_the_timestep_changes() {
+        // moving at one unit per second after a step of 0.016
+        let node = Verlet::new(0.0, 0.0).with_parts(Point2::new(0.0, 0.0), Point2::new(-0.016, 0.0), false).with_last_step(0.016);
+        let node = Arc::new(RwLock::new(Node::new(node)));
+        let coast: Arc<dyn SelfStepResolve<Verlet>> = Arc::new(Constraint::Gravity { gravity: 0.0, delta: None });
+        let mut mesh = Mesh::new(vec!(Arc::clone(&node)), vec!(), vec!(SelfConnector::stepped("coast", Arc::clone(&node), coast)));
+
+        for (dt, x) in [(0.016, 0.016), (0.033, 0.049), (0.016, 0.065)] {
+            mesh.update(dt, 1).unwrap();
+            let data = node.read().unwrap().data;
+            assert!((data.position.x - x).abs() < 1e-12);
+            assert!((Kinematic::velocity(&data, dt).x - 1.0).abs() < 1e-9);
+        }
+    }
+}